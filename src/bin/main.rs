@@ -1,62 +1,35 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use computerhardwaredb::{
     types::{Bytes, CPU, Watt, GHz, CoolerType, SoldAt, CPUSocket, ExtensionBus,
-            MemoryType, MTperSec, Shop, GraphicsModel, Price, MemorySubtype, MarketSegment,
-            Architecture},
+            MemoryType, MTperSec, Shop, Country, Currency, GraphicsModel, Price, MemorySubtype, MarketSegment,
+            Architecture, CoreCluster, Benchmark, Motherboard, MemoryKit},
     value::Value,
+    date::Date,
+    database::{Database, Data},
+    build::{self, BuildParams},
+    lint::Suppression,
+    report::Report,
+    collectsorted::{unsafe_cmp, on, CollectSortedPartitioned},
+    scoring::{self, Profile, ProfileParams, ProfileConfig, Performance},
+    tco::{self, Tco},
+    calibration,
+    uncertainty::{self, UncertaintyParams, OfferUncertainty},
+    pareto::pareto_front,
+    policy::{self, OfferPolicy},
+    landed::{self, LandedParams, LandedPrice},
     index::{pindex_by, mindex_by_foreign},
-    collectsorted::{unsafe_cmp, on, CollectSorted}
 };
 
 
-/// A performance estimate score, higher is better, that works for
-/// comparison across CPU families and is inversely proportional to
-/// time required to compile a single Rust or C++ package on that CPU
-/// (using all of its threads as much as compilation allows, assuming
-/// use of the mold linker), i.e. simulating rebuilds of a project
-/// where a random file is changed (header file in C++ -> rebuild of
-/// many of the object files; rebuild of the lib crate and maybe also
-/// the app crate in Rust, relying on the parallel stage in LLVM for
-/// concurrency, as well as hoping somewhat for future parallelization
-/// of the frontend).
-fn anticipated_compilation_performance(cpu: &CPU) -> Result<f32> {
-    let coresthreads = {
-        let cores = *cpu.cores.value()? as f32;
-        let threads = *cpu.threads.value()? as f32;
-        let additional_threads = threads - cores;
-        
-        cores + additional_threads * 0.3
-    };
-    // XX memory channels  compared to threads!
-
-    let base_clock = cpu.base_clock.value()?;
-    // let max_boost_clock = cpu.max_boost_clock.value()?;
-    // when highly parallel, only base clock usable, right?
-    // which is most of my compiler workload.
-
-    let factor = {
-        let launch_date_sec = cpu.launch_date.value()?.unixtime();
-        // Twice as fast per core every 5 years? In the last 5y anyway? Or
-        // should rather look at the architecture, right?
-        let launch_date_years: f32 = (launch_date_sec as f32) / (365.*24.*3600.);
-        let periods: f32 = launch_date_years / 5.;
-        f32::powf(2.0, periods)
-    };
-
-    // XX PCIe
-
-    Ok(coresthreads * base_clock.0 * factor)
-}
-
-
-fn main() -> Result<()> {
+#[allow(clippy::useless_conversion)]
+fn database() -> Result<Database> {
     let t = true;
     let f = false;
     use Bytes::*;
-    use Value::Missing;
+    use Value::{Missing, NotApplicable};
     use ExtensionBus::PCIe;
 
-    let cpus: &[CPU] = &[
+    let cpus: Vec<CPU> = vec![
         CPU {
             name: "AMD Ryzen™ 9 5950X".into(),
             // https://www.amd.com/en/products/cpu/amd-ryzen-9-5950x
@@ -65,16 +38,18 @@ fn main() -> Result<()> {
             desc: "".into(),
             cores: 16.into(),
             threads: 32.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
             l2cache: MB(8).into(),
             l3cache: MB(64).into(),
             tdp: Watt(105).into(),
             base_clock: GHz(3.4).into(),
-            max_boost_clock: Missing,
+            max_boost_clock: GHz(4.9).into(),
             cooler: CoolerType::LiquidRecommended.into(),
             launch_date: "11/5/2020".try_into()?,
             cpu_socket: CPUSocket::AM4.into(),
-            memory_channels: Missing,
+            max_sockets: 1.into(),
+            memory_channels: 2.into(),
             pci_express_version: PCIe(4.0, Missing).into(),
             system_memory_type: MemoryType::DDR4.into(),
             system_memory_specification: MTperSec(3200).into(),
@@ -94,6 +69,7 @@ fn main() -> Result<()> {
             desc: "".into(),
             cores: 12.into(),
             threads: 24.into(),
+            core_clusters: NotApplicable,
             l1cache: KB(768).into(),
             l2cache: MB(12).into(),
             l3cache: MB(64).into(),
@@ -103,6 +79,7 @@ fn main() -> Result<()> {
             cooler: Missing,
             launch_date: "6/13/2023".try_into()?,
             cpu_socket: CPUSocket::AM5.into(),
+            max_sockets: 1.into(),
             memory_channels: 2.into(),
             pci_express_version: PCIe(
                 5.0,
@@ -115,7 +92,7 @@ AMD B650   8x Gen4".into())
             ).into(),
             system_memory_type: MemoryType::DDR5.into(),
             system_memory_subtype: MemorySubtype::UDIMM.into(),
-            system_memory_specification: Missing,
+            system_memory_specification: MTperSec(5200).into(),
             ecc_support: t.into(),
             graphics_model: GraphicsModel::Radeon.into(),
             market_segment: Missing,
@@ -131,6 +108,7 @@ AMD B650   8x Gen4".into())
             desc: "".into(),
             cores: 16.into(),
             threads: 32.into(),
+            core_clusters: NotApplicable,
             l1cache: MB(1).into(),
             l2cache: MB(8).into(),
             l3cache: MB(64).into(),
@@ -140,6 +118,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "3/8/2022".try_into()?,
             cpu_socket: "sWRX8".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, Missing).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -161,6 +140,7 @@ AMD B650   8x Gen4".into())
             desc: "".into(),
             cores: 16.into(),
             threads: 32.into(),
+            core_clusters: NotApplicable,
             l1cache: MB(1).into(),
             l2cache: MB(16).into(),
             l3cache: MB(128).into(),
@@ -170,11 +150,12 @@ AMD B650   8x Gen4".into())
             cooler: CoolerType::LiquidRecommended.into(),
             launch_date: "2/28/2023".try_into()?,
             cpu_socket: "AM5".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 2.into(),
             pci_express_version: PCIe(5.0, Missing).into(),
             system_memory_type: MemoryType::DDR5.into(),
             system_memory_subtype: MemorySubtype::UDIMM.into(),
-            system_memory_specification: Missing,
+            system_memory_specification: MTperSec(5200).into(),
             ecc_support: t.into(),
             graphics_model: GraphicsModel::Radeon.into(),
             architecture: Architecture::Zen4.into(),
@@ -195,6 +176,7 @@ AMD B650   8x Gen4".into())
             desc: "".into(),
             cores: 16.into(),
             threads: 32.into(),
+            core_clusters: NotApplicable,
             l1cache: MB(1).into(),
             l2cache: MB(8).into(),
             l3cache: MB(64).into(),
@@ -204,11 +186,12 @@ AMD B650   8x Gen4".into())
             cooler: CoolerType::LiquidRecommended .into(),
             launch_date: "7/7/2019".try_into()?,
             cpu_socket: "AM4".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 2.into(),
             pci_express_version: PCIe(4.0, Missing).into(),
             system_memory_type: MemoryType::DDR4.into(),
             system_memory_subtype: MemorySubtype::UDIMM.into(),
-            system_memory_specification: Missing,
+            system_memory_specification: MTperSec(3200).into(),
             ecc_support: t.into(),
             graphics_model: GraphicsModel::None.into(),
             pcie_dma_security: t.into(),
@@ -222,12 +205,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-7502p".into(),
             market_segment: Missing,
             product_line: "AMD EPYC™ 7002 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 2".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 32.into(),
             threads: 64.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(16).into(), // 512 KB per core (Zen 2)
             l3cache: MB(128).into(),
             tdp: Watt(180).into(),
             base_clock: GHz(2.5).into(),
@@ -237,6 +221,7 @@ AMD B650   8x Gen4".into())
                 "August 7, 2019".try_into()?,
                 "from https://en.wikipedia.org/wiki/Zen_2".into()),
             cpu_socket: "SP3".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -254,19 +239,21 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-8224p".into(),
             market_segment: MarketSegment::Server.into(), //  Platform  Server
             product_line: "AMD EPYC™ 8004 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 4c".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(24).into(), // 1 MB per core (Zen 4c)
             l3cache: MB(64).into(),
             tdp: Watt(160).into(),
             base_clock: GHz(2.55).into(),
             max_boost_clock: GHz(3.0).into(),
             cooler: Missing,
             launch_date: "9/18/2023".try_into()?,
-            cpu_socket: "AM4".try_into()?,
+            cpu_socket: "SP6".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 6.into(),
             pci_express_version: PCIe(5.0, 96.into()).into(),
             system_memory_type: MemoryType::DDR5.into(),
@@ -285,12 +272,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-7443".into(),
             market_segment: MarketSegment::Server.into(),
             product_line: "AMD EPYC™ 7003 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 3".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(12).into(), // 512 KB per core (Zen 3)
             l3cache: MB(128).into(),
             tdp: Watt(200).into(), // AMD Configurable TDP (cTDP)  165-200W
             base_clock: GHz(2.85).into(),
@@ -298,6 +286,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "3/15/2021".try_into()?,
             cpu_socket: "SP3".try_into()?,
+            max_sockets: 2.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -315,12 +304,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-7313p".into(),
             market_segment: MarketSegment::Server.into(),
             product_line: "AMD EPYC™ 7003 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 3".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 16.into(),
             threads: 32.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(8).into(), // 512 KB per core (Zen 3)
             l3cache: MB(128).into(),
             tdp: Watt(155).into(), // AMD Configurable TDP (cTDP)  155-180W
             base_clock: GHz(3.0).into(),
@@ -328,6 +318,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "3/15/2021".try_into()?,
             cpu_socket: "SP3".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -345,12 +336,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-7352".into(),
             market_segment: MarketSegment::Server.into(),
             product_line: "AMD EPYC™ 7002 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 2".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(12).into(), // 512 KB per core (Zen 2)
             l3cache: MB(128).into(),
             tdp: Watt(155).into(),
             base_clock: GHz(2.3).into(),
@@ -361,6 +353,7 @@ AMD B650   8x Gen4".into())
                 "from https://en.wikipedia.org/wiki/Zen_2".into()
             ),
             cpu_socket: "SP3".try_into()?,
+            max_sockets: 2.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -378,12 +371,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-9224".into(),
             market_segment: MarketSegment::Server.into(),
             product_line: "AMD EPYC™ 9004 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 4".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(24).into(), // 1 MB per core (Zen 4)
             l3cache: MB(64).into(),
             tdp: Watt(200).into(),
             base_clock: GHz(2.5).into(),
@@ -391,6 +385,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "11/10/2022".try_into()?,
             cpu_socket: "SP5".try_into()?,
+            max_sockets: 2.into(),
             memory_channels: 12.into(),
             pci_express_version: PCIe(5.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR5.into(),
@@ -408,12 +403,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-7443P".into(),
             market_segment: MarketSegment::Server.into(),
             product_line: "AMD EPYC™ 7003 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 3".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(12).into(), // 512 KB per core (Zen 3)
             l3cache: MB(128).into(),
             tdp: Watt(200).into(), // AMD Configurable TDP (cTDP) 165-200W
             base_clock: GHz(2.85).into(),
@@ -421,6 +417,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "3/15/2021".try_into()?,
             cpu_socket: "SP3".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -438,12 +435,13 @@ AMD B650   8x Gen4".into())
             url: "https://www.amd.com/en/products/cpu/amd-epyc-7513".into(),
             market_segment: MarketSegment::Server.into(),
             product_line: "AMD EPYC™ 7003 Series".try_into()?,
-            architecture: "AMD Infinity Architecture".try_into()?,
+            architecture: "Zen 3".try_into()?, // AMD: "AMD Infinity Architecture"
             desc: "".into(),
             cores: 32.into(),
             threads: 64.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(16).into(), // 512 KB per core (Zen 3)
             l3cache: MB(128).into(),
             tdp: Watt(200).into(),
             base_clock: GHz(2.6).into(),
@@ -451,6 +449,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "3/15/2021".try_into()?,
             cpu_socket: "SP3".try_into()?,
+            max_sockets: 2.into(),
             memory_channels: 8.into(),
             pci_express_version: PCIe(4.0, 128.into()).into(),
             system_memory_type: MemoryType::DDR4.into(),
@@ -472,6 +471,7 @@ AMD B650   8x Gen4".into())
             desc: "".into(),
             cores: 16.into(),
             threads: 32.into(),
+            core_clusters: NotApplicable,
             l1cache: MB(1).into(),
             l2cache: MB(16).into(),
             l3cache: MB(64).into(),
@@ -481,11 +481,12 @@ AMD B650   8x Gen4".into())
             cooler: CoolerType::LiquidRecommended.into(),
             launch_date: "9/27/2022".try_into()?,
             cpu_socket: "AM5".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 2.into(),
             pci_express_version: PCIe(5.0, Missing).into(),
             system_memory_type: MemoryType::DDR5.into(),
             system_memory_subtype: MemorySubtype::UDIMM.into(),
-            system_memory_specification: Missing,
+            system_memory_specification: MTperSec(5200).into(),
             ecc_support: t.into(),
             graphics_model: GraphicsModel::Radeon.into(),
             pcie_dma_security: Missing,
@@ -503,6 +504,7 @@ AMD B650   8x Gen4".into())
             desc: "".into(),
             cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: KB(1536).into(),
             l2cache: MB(24).into(),
             l3cache: MB(128).into(),
@@ -512,6 +514,7 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "10/19/2023".try_into()?,
             cpu_socket: "sTR5".try_into()?,
+            max_sockets: 1.into(),
             memory_channels: 4.into(),
             pci_express_version: PCIe(5.0, Missing).into(),
             system_memory_type: MemoryType::DDR5.into(),
@@ -530,13 +533,15 @@ AMD B650   8x Gen4".into())
             name: "Intel 6248R".into(),
             url: "https://ark.intel.com/content/www/us/en/ark/products/199351/intel-xeon-gold-6248r-processor-35-75m-cache-3-00-ghz.html".into(),
             market_segment: MarketSegment::Server.into(), //  "Vertical Segment"
-            product_line: Missing, // What would that be for Intel?
-            architecture: Missing, // What would that be for Intel?
+            // "Product Collection"
+            product_line: "2nd Generation Intel® Xeon® Scalable Processors".try_into()?,
+            architecture: "Products formerly Cascade Lake".try_into()?, // "Code Name"
             desc: "".into(),
-            cores: 24.into(), // XX how many performance vs efficiency scores ?
+            cores: 24.into(),
             threads: 48.into(),
+            core_clusters: NotApplicable,
             l1cache: Missing,
-            l2cache: Missing,
+            l2cache: MB(24).into(), // 1 MB per core (Cascade Lake)
             l3cache: KB(36608).into(), // "Cache" "35.75 MB", but is probably *total*
             tdp: Watt(205).into(),
             base_clock: GHz(3.0).into(),
@@ -544,12 +549,13 @@ AMD B650   8x Gen4".into())
             cooler: Missing,
             launch_date: "Q1'20".try_into()?,
             cpu_socket: "FCLGA3647".try_into()?,
+            max_sockets: 2.into(),
             memory_channels: 6.into(), // "Max # of Memory Channels", max? ah if all slots used?
             pci_express_version: PCIe(3.5, 48.into()).into(),
             system_memory_type: MemoryType::DDR4.into(), // DDR4-2933
             system_memory_subtype: Missing,
             //  Max Memory Size (dependent on memory type) 1 TB
-            system_memory_specification: Missing,
+            system_memory_specification: MTperSec(2933).into(),
             ecc_support: t.into(),
             graphics_model: Missing,
             pcie_dma_security: Missing,
@@ -557,6 +563,57 @@ AMD B650   8x Gen4".into())
             amd_enhanced_virus_protection_nx_bit: Missing,
         },
 
+        CPU {
+            name: "Intel Core i9-13900K".into(),
+            url: "https://ark.intel.com/content/www/us/en/ark/products/230496/intel-core-i913900k-processor-36m-cache-up-to-5-80-ghz.html".into(),
+            market_segment: MarketSegment::Desktop.into(),
+            product_line: "13th Generation Intel® Core™ i9 Processors".try_into()?,
+            architecture: "Products formerly Raptor Lake".try_into()?,
+            desc: "".into(),
+            cores: 24.into(),
+            threads: 32.into(),
+            core_clusters: vec![
+                CoreCluster {
+                    count: 8, // "# of Performance-cores"
+                    threads_per_core: 2,
+                    architecture: "Raptor Cove".try_into()?,
+                    base_clock: GHz(3.0).into(),
+                    max_boost_clock: GHz(5.4).into(), // 5.8 with Thermal Velocity Boost
+                    l2cache: MB(16).into(),
+                    l3cache: MB(36).into(),
+                },
+                CoreCluster {
+                    count: 16, // "# of Efficient-cores"
+                    threads_per_core: 1,
+                    architecture: "Gracemont".try_into()?,
+                    base_clock: GHz(2.2).into(),
+                    max_boost_clock: GHz(4.3).into(),
+                    l2cache: MB(16).into(),
+                    l3cache: MB(36).into(),
+                },
+            ].into(),
+            l1cache: Missing,
+            l2cache: MB(32).into(), // "Total L2 Cache"
+            l3cache: MB(36).into(), // "Cache  36 MB Intel® Smart Cache"
+            tdp: Watt(125).into(), // "Processor Base Power"; "Maximum Turbo Power" 253 W
+            base_clock: GHz(3.0).into(), // "Performance-core Base Frequency"
+            max_boost_clock: GHz(5.8).into(),
+            cooler: Missing,
+            launch_date: "Q4'22".try_into()?,
+            cpu_socket: "FCLGA1700".try_into()?,
+            max_sockets: 1.into(),
+            memory_channels: 2.into(),
+            pci_express_version: PCIe(5.0, 20.into()).into(),
+            system_memory_type: MemoryType::DDR5.into(), // "Up to DDR5 5600 MT/s", or DDR4 3200
+            system_memory_subtype: MemorySubtype::UDIMM.into(),
+            system_memory_specification: MTperSec(5600).into(),
+            ecc_support: t.into(),
+            graphics_model: GraphicsModel::IntelUHD.into(),
+            pcie_dma_security: Missing,
+            usb_dma_security: Missing,
+            amd_enhanced_virus_protection_nx_bit: Missing,
+        },
+
         // CPU {
         //     name: "".into(),
         //     url: "".into(),
@@ -565,6 +622,7 @@ AMD B650   8x Gen4".into())
         //     architecture: "".try_into()?,
         //     desc: "".into(),
         //     cores: .into(),
+        //     core_clusters: NotApplicable,
         //     threads: .into(),
         //     l1cache: .into(),
         //     l2cache: .into(),
@@ -575,6 +633,7 @@ AMD B650   8x Gen4".into())
         //     cooler: .into(),
         //     launch_date: "".try_into()?,
         //     cpu_socket: "".try_into()?,
+        //     max_sockets: 1.into(),
         //     memory_channels: .into(),
         //     pci_express_version: PCIe().into(),
         //     system_memory_type: MemoryType::.into(),
@@ -589,132 +648,176 @@ AMD B650   8x Gen4".into())
         
     ];
 
+    let shops: Vec<Shop> = vec![
+        Shop {
+            name: "Digitec".into(),
+            url_domain: "digitec.ch".into(),
+            country: Country::CH,
+            currency: Currency::CHF,
+            shipping: Missing,
+            prices_include_vat: t,
+        },
+        Shop {
+            name: "Brack".into(),
+            url_domain: "brack.ch".into(),
+            country: Country::CH,
+            currency: Currency::CHF,
+            shipping: Missing,
+            prices_include_vat: t,
+        },
+    ];
+
     use Price::*;
-    let sold_at: &[SoldAt] = &[
+    // The day the offers below were collected was not recorded; the
+    // weekdays in their delivery dates fit 2024, and the earliest is
+    // Tue 2.4.
+    let retrieved = || Value::SomeWithDoubts(
+        Date::new(2024, 4, 1).unwrap(), "guessed from the delivery dates".into());
+    let sold_at: Vec<SoldAt> = vec![
         SoldAt {
             article_name: "AMD Ryzen™ 9 5950X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             desc: "AMD Ryzen 9 5950X
 AM4, 3.40 GHz, 16 -Core".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-5950x-am4-340-ghz-16-core-processors-13987916".into(),
             price: CHF(366).into(),
             is_tray_version: f.into(),
             is_used: f.into(),
+            warranty_months: Missing,
             delivered: "Delivered the day after tomorrow
 10 items in stock".into(),
+            retrieved: retrieved(),
         },
 
         SoldAt {
             article_name: "AMD Ryzen™ 9 5950X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             desc: "AMD Ryzen 9 5950X
 AM4, 3.40 GHz, 16 -Core".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-5950x-am4-340-ghz-16-core-processors-13987916?shid=1399419".into(),
             price: CHF(329).into(),
             is_tray_version: f.into(),
             is_used: t.into(),
+            warranty_months: Missing,
             delivered: "Delivered the day after tomorrow
 Only 1 item in stock".into(),
+            retrieved: retrieved(),
         },
 
         SoldAt {
             article_name: "AMD Ryzen™ 9 5950X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             desc: "AMD Ryzen 9 5950X
 AM4, 3.40 GHz, 16 -Core".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-5950x-34-ghz-am4-340-ghz-16-core-processors-31176126".into(),
             price: CHF(614).into(),
             is_tray_version: f.into(),
             is_used: f.into(),
+            warranty_months: Missing,
             delivered: "Delivered between Fri 5.4. and Thu 11.4.
 7 items in stock at third-party supplier
 Supplied byJACOB DE".into(), // so bad, be careful about 3rd party, so bad?
+            retrieved: retrieved(),
         },
 
         SoldAt {
             article_name: "AMD Ryzen™ 9 5950X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             desc: "AMD CPU Ryzen 9 5950X 3.4GHz 16-core AM4
 AM4, 3.40 GHz, 16 -Core".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-cpu-ryzen-9-5950x-34ghz-16-core-am4-am4-340-ghz-16-core-processors-36137541".into(),
             price: CHF(860).into(),
             is_tray_version: f.into(),
             is_used: f.into(),
+            warranty_months: Missing,
             delivered: "Delivered between Thu 18.4. and Wed 1.5.
 More than 10 items in stock at supplier".into(), // even worse.  be careful ??
+            retrieved: retrieved(),
         },
 
         SoldAt {
             article_name: "AMD Ryzen 9 PRO 7945".into(),
             desc: "AMD Ryzen 9 Pro 7945 Tray Version AM5, 3.70 GHz, 12 -Core".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-pro-7945-tray-version-am5-370-ghz-12-core-processors-37097588".into(),
             price: CHF(481),
             is_tray_version: true,
             is_used: false,
+            warranty_months: Missing,
             delivered: "Delivered Wed 3.4. Only 1 item in stock".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             article_name: "AMD Ryzen Threadripper PRO 5955WX".into(),
             desc: "AMD Ryzen ThreadRipper PRO 5955WX - 4 GHz - 16 cores - 32 threads - 64 MB cache memory - Socket sWRX8 - OEM.".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-threadripper-pro-5955wx-4-gh-swrx8-4-ghz-16-core-processors-23263816".into(),
             price: CHF(966),
             is_tray_version: false,
             is_used: false,
+            warranty_months: Missing,
             delivered: "Delivered between Wed 10.4. and Sat 13.4.
 Only 1 item in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             article_name: "AMD Ryzen 9 7950X3D".into(),
             desc: "AMD AM5 Ryzen 9 7950X3D
 Tray 5.7GHz 16xCore 144MB 120W
 AM5, 4.20 GHz, 16 -Core".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-am5-ryzen-9-7950x3d-tray-57ghz-16xcore-144mb-120w-am5-420-ghz-16-core-processors-36941584".into(),
             price: CHF(791),
             is_tray_version: true,
             is_used: false,
+            warranty_months: Missing,
             delivered: "Delivered between Thu 4.4. and Thu 11.4.
 More than 10 items in stock at third-party supplier
 Supplied byorderflow.ch CH".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             article_name: "AMD Ryzen 9 3950X".into(),
             desc: "last new 1269.–
 
 AM4, 3.50 GHz, 16 -Core".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-3950x-am4-350-ghz-16-core-processors-11239808?shid=1383800".into(),
             price: CHF(1070),
             is_tray_version: false,
             is_used: true,
+            warranty_months: Missing,
             delivered: "Delivered between Tue 2.4. and Mon 8.4.
 loicbujard9
 Buy used from
 loicbujard9 · Member since 2014".into(), // Oh careful
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-threadripper-pro-5955wx-swrx8-4-ghz-16-core-processors-22516524".into(),
             article_name: "AMD Ryzen Threadripper PRO 5955WX".into(),
             desc: "sWRX8, 4 GHz, 16 -Core".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(997),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Fri 12.4. and Wed 24.4.
 5 items in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-7502p-sp3-250-ghz-32-core-processors-12279505".into(),
             article_name: "AMD EPYC 7502P".into(),
             desc: "SP3, 2.50 GHz, 32 -Core".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1121),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Thu 4.4. and Thu 11.4.
 5 items in stock at third-party supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-7502p-25ghz-tray-sp3-250-ghz-32-core-processors-20922660".into(),
@@ -722,26 +825,30 @@ loicbujard9 · Member since 2014".into(), // Oh careful
             desc: " - 2.5GHz (Tray)
 SP3, 2.50 GHz, 32 -Core
 Socket SP3 / 32 core / 64 threads / 128MB cache / 180W TDP.".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1045),
             is_tray_version: t,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Tue 2.4. and Thu 4.4.
 5 items in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-8224p-255-ghz-24-cores-4-sp6-255-ghz-24-core-processors-40944724".into(),
             desc: " - 2.55 GHz - 24 cores - 4
 SP6, 2.55 GHz, 24 -Core".into(),
             article_name: "AMD EPYC 8224P".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1023),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Fri 5.4. and Fri 12.4.
 6 items in stock at third-party supplier
 Supplied by
 JACOB DE".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-7950x3d-42-ghz-16-cores-am5-420-ghz-16-core-processors-32888396".into(),
@@ -750,26 +857,30 @@ JACOB DE".into(),
 AM5, 4.20 GHz, 16 -Core
 ".into(),
             article_name: "AMD Ryzen 9 7950X3D".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(815),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Fri 5.4. and Fri 12.4.
 More than 10 items in stock at third-party supplier
 Supplied by
 JACOB DE".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-7443-tray-4-units-only-sp3-285-ghz-24-core-processors-15655850".into(),
             desc: "AMD Epyc 7443 Tray 4 units only  <-- XX
 SP3, 2.85 GHz, 24 -Core".into(),
             article_name: "AMD EPYC 7443".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1224),
             is_tray_version: t,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Tue 2.4. and Thu 4.4.
 Only 1 item in stock at supplie".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-16-core-73711p-36ghz-sp3-240-ghz-16-core-processors-10892979".into(),
@@ -778,25 +889,29 @@ Only 1 item in stock at supplie".into(),
 SP3, 2.40 GHz, 16 -Core
 EPYC 7351, 16C/32T, 2.4GHz (2.9GHz Max), 64MB L3 Cache, 170W.".into(),
             article_name: "AMD EPYC 73711P".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(509),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "
 Delivered between Wed 10.4. and Tue 16.4.
 6 items in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-7352-sp3-230-ghz-24-core-processors-12279514".into(),
             desc: "AMD Epyc 7352
 SP3, 2.30 GHz, 24 -Core".into(),
             article_name: "AMD EPYC 7352".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(753),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Wed 10.4. and Tue 16.4.
 Only 4 items in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-9224-25-ghz-24-cores-48-sp5-250-ghz-48-core-processors-32425504".into(),
@@ -804,12 +919,14 @@ Only 4 items in stock at supplier".into(),
 SP5, 2.50 GHz, 48 -Core
 AMD EPYC 9224 - 2.5 GHz - 24 cores - 48 threads - 64 MB cache memory - Socket SP5 - OEM.".into(),
             article_name: "AMD EPYC 9224".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1755),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Sat 13.4. and Wed 1.5.
 Only 4 items in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-24core-model-7443p-sp3-box-sp3-285-ghz-24-core-processors-37552911".into(),
@@ -817,11 +934,13 @@ Only 4 items in stock at supplier".into(),
 SP3 BOX
 SP3, 2.85 GHz, 24 -Core".into(),
             article_name: "AMD EPYC 7443P".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1336),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Wed 22.5. and Thu 4.7. XX".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-epyc-7513-26-ghz-sp3-260-ghz-32-core-processors-21973612".into(),
@@ -829,22 +948,26 @@ SP3, 2.85 GHz, 24 -Core".into(),
 SP3, 2.60 GHz, 32 -Core
 ".into(),
             article_name: "AMD EPYC 7513".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(839),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Tue 14.5. and Wed 29.5.".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-7950x3d-am5-420-ghz-16-core-processors-24107476".into(),
             desc: "AM5, 4.20 GHz, 16 -Core".into(),
             article_name: "AMD Ryzen 9 7950X3D".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(570),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered Wed 3.4.
 More than 10 items in stock".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-9-7950x-am5-450-ghz-16-core-processors-21918730".into(),
@@ -852,12 +975,14 @@ More than 10 items in stock".into(),
 16-Core / 32-Threads / 4.5Hz / Socket AM5.
 ".into(),
             article_name: "AMD Ryzen 9 7950X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(511),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered Wed 3.4.
 More than 10 items in stock".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-ryzen-tr-7960x-tray-8-units-str5-420-ghz-24-core-processors-40183078".into(),
@@ -865,12 +990,14 @@ More than 10 items in stock".into(),
 sTR5, 4.20 GHz, 24 -Core
 AMD Ryzen TR 7960X Tray 8 units.".into(),
             article_name: "AMD Ryzen Threadripper 7960X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1520),
             is_tray_version: t,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered between Wed 10.4. and Tue 16.4.
 5 items in stock at supplier".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/amd-threadripper-7960x-str5-str5-420-ghz-24-core-processors-39441097".into(),
@@ -878,12 +1005,14 @@ AMD Ryzen TR 7960X Tray 8 units.".into(),
 sTR5, 4.20 GHz, 24 -Core
 24C 5.3GHZ 152MB 350W WOF.".into(),
             article_name: "AMD Ryzen Threadripper 7960X".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1400),
             is_tray_version: f,
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered Wed 3.4.
 Only 2 items in stock".into(),
+            retrieved: retrieved(),
         },
         SoldAt {
             url: "https://www.digitec.ch/en/s1/product/intel-intel-xeon-6248r-lga-3647-3-ghz-24-core-processors-14053584".into(),
@@ -895,33 +1024,808 @@ Intel Intel Xeon 6248R
 LGA 3647, 3 GHz, 24 -Core
 INTEL Xeon Gold 6248R 3.0GHz FC-LGA3647 35.75M Cache Tray CPU.".into(),
             article_name: "Intel 6248R".into(),
-            shop: Shop::Digitec,
+            shop: "Digitec".into(),
             price: CHF(1159),
             is_tray_version: f, // XX well, t but OK
             is_used: f,
+            warranty_months: Missing,
             delivered: "Delivered Wed 3.4.
 Only 1 item in stock".into(),
+            retrieved: retrieved(),
         },
 
     ];
-    let cpus_by_name = pindex_by(cpus, |s| &s.name)?;
-    let sold_at_by_article_name = mindex_by_foreign(
-        sold_at, |s| &s.article_name, &cpus_by_name, "SoldAt.article_name -> Article.name")?;
-    for cpu in cpus {
-        if ! sold_at_by_article_name.contains_key(&cpu.name) {
-            println!("WARNING: cpu {:?} is not being sold", &cpu.name);
+    // Results from reviews, for the `validate` command. Only enter
+    // numbers actually published, with the URL of the review.
+    let benchmarks: Vec<Benchmark> = vec![
+        // Benchmark {
+        //     key: BenchmarkKey {
+        //         cpu: "".into(),
+        //         benchmark: "Linux kernel build (defconfig)".into(),
+        //         source_url: "".into(),
+        //     },
+        //     result: ,
+        //     units: BenchmarkUnit::Seconds,
+        //     date: "".try_into()?,
+        // },
+    ];
+
+    // For the `build` command. Offers are `SoldAt` rows like for
+    // CPUs, with `article_name` referencing the `name` of the part.
+    let motherboards: Vec<Motherboard> = vec![
+        // Motherboard {
+        //     name: "".into(),
+        //     url: "".into(),
+        //     cpu_socket: CPUSocket::AM5,
+        //     memory_type: MemoryType::DDR5,
+        //     memory_subtype: MemorySubtype::UDIMM.into(),
+        //     memory_slots: 4,
+        //     memory_channels: 2,
+        //     max_memory_gb: 192.into(),
+        // },
+    ];
+    let motherboards_sold_at: Vec<SoldAt> = vec![
+    ];
+    let memory_kits: Vec<MemoryKit> = vec![
+        // MemoryKit {
+        //     name: "".into(),
+        //     url: "".into(),
+        //     memory_type: MemoryType::DDR5,
+        //     memory_subtype: MemorySubtype::UDIMM.into(),
+        //     modules: 2,
+        //     module_gb: 32,
+        //     speed: MTperSec(5600),
+        // },
+    ];
+    let memory_kits_sold_at: Vec<SoldAt> = vec![
+    ];
+
+    let lint_suppressions: Vec<Suppression> = vec![
+        // Suppression {
+        //     rule: "",
+        //     record: "",
+        //     reason: "",
+        // },
+    ];
+
+    Database::new(Data {
+        shops, cpus, sold_at, benchmarks, motherboards, motherboards_sold_at, memory_kits,
+        memory_kits_sold_at, lint_suppressions
+    })
+}
+
+
+fn lint(db: &Database) -> Result<()> {
+    let mut report = Report::default();
+    let suppressed = db.lint(&mut report);
+    report.print();
+    for (violation, reason) in &suppressed {
+        println!("suppressed: {violation}: {reason}");
+    }
+    println!("{} error(s), {} warning(s), {} suppressed",
+             report.errors().count(), report.warnings().count(), suppressed.len());
+    report.into_result()
+}
+
+
+#[derive(Debug)]
+#[allow(dead_code)] // fields are only shown via Debug
+struct RankedOffer<'t> {
+    offer: &'t SoldAt,
+    performance: Performance,
+    /// Applied to the score for the values, from the policy for used
+    /// and tray offers
+    risk_factor: f32,
+    /// Of all sockets, delivered to the destination
+    landed: LandedPrice,
+    /// Extra cost of a multi-socket machine, 0 for one socket
+    platform_chf: f32,
+    /// Score per CHF of landed price plus `platform_chf`
+    value: f32,
+    tco: Option<Tco>,
+    /// Score per CHF of total cost of ownership
+    value_tco: Option<f32>,
+    warranty_months: Value<u8>,
+    cooler: Option<&'static str>,
+    uncertainty: Option<OfferUncertainty>,
+}
+
+impl RankedOffer<'_> {
+    fn sort_key(&self) -> &f32 {
+        self.value_tco.as_ref().unwrap_or(&self.value)
+    }
+
+    /// What the value is relative to, in CHF
+    fn cost(&self) -> f32 {
+        match &self.tco {
+            Some(t) => t.total_chf,
+            None => self.landed.total_chf + self.platform_chf,
         }
     }
+}
+
+/// "2P " etc. for multi-socket configurations, empty for one socket.
+fn socket_prefix(sockets: u8) -> String {
+    if sockets > 1 { format!("{sockets}P ") } else { String::new() }
+}
+
+/// Each of `offers` with each number of sockets its CPU can be used
+/// with (just 1 if the CPU is not found).
+fn with_socket_configurations<'t>(
+    db: &'t Database, offers: impl IntoIterator<Item = &'t SoldAt>
+) -> Vec<(&'t SoldAt, u8)> {
+    offers.into_iter().flat_map(|offer| {
+        let sockets = db.cpu_of(offer).map(|cpu| cpu.socket_configurations())
+            .unwrap_or(vec![1]);
+        sockets.into_iter().map(move |n| (offer, n))
+    }).collect()
+}
+
+/// An offer that can't be scored, and why (e.g. the fields required
+/// for scoring that are missing in the CPU record).
+struct Unscoreable<'t> {
+    offer: &'t SoldAt,
+    error: anyhow::Error,
+}
 
-    let offers = sold_at.iter().map(|offer| -> Result<_> {
-        let cpu = cpus_by_name.get(&offer.article_name).expect("checked already");
-        let perf = anticipated_compilation_performance(cpu)?;
-        let value = perf / (offer.price.in_chf() as f32);
-        Ok((offer, perf, value))
+/// Sorted by value for money, taking the electricity costs into
+/// account if `tco` is true, and with score intervals if
+/// `uncertainty_params` is given. Offers of multi-socket CPUs are
+/// also ranked as 2P (etc.) machines, with the multi-socket premium
+/// of `config` added to their price. Used and tray offers are
+/// excluded or discounted according to the policies in `config`.
+/// Prices are landed prices, delivered as given in `config`.
+fn rank(
+    db: &Database,
+    config: &ProfileConfig,
+    tco: bool,
+    uncertainty_params: Option<&UncertaintyParams>,
+) -> Result<()> {
+    let (profile, params, policies) = (config.profile, &config.params, &config.offers);
+    let tco_params = tco.then_some(&config.tco);
+    let (included, excluded): (Vec<_>, Vec<_>) = db.sold_at.rows().iter()
+        .partition(|offer| policies.risk_factor(offer).is_some());
+    let configurations = with_socket_configurations(db, included);
+    let offers = configurations.into_iter().map(|(offer, sockets)| {
+        (|| -> Result<_> {
+            let cpu = db.cpu_of(offer)?;
+            let performance = profile.performance_with_sockets(params, cpu, sockets)?;
+            let risk_factor = policies.risk_factor(offer).expect("excluded above");
+            let score = performance.score * risk_factor;
+            let landed = landed::landed_price(&config.landed, db.shop_of(offer)?, offer.price,
+                                              sockets);
+            let platform_chf = config.platform_chf(sockets);
+            let purchase_chf = landed.total_chf + platform_chf;
+            let value = score / purchase_chf;
+            let tco = tco_params.map(|p| tco::tco(p, cpu, sockets, purchase_chf))
+                .transpose()?;
+            let value_tco = tco.as_ref().map(|t| score / t.total_chf);
+            Ok(RankedOffer {
+                offer, performance, risk_factor, landed, platform_chf, value, tco, value_tco,
+                warranty_months: offer.warranty_months.clone(),
+                cooler: policy::cooler_note(offer, cpu),
+                uncertainty: None,
+            })
+        })().map_err(|error| Unscoreable { offer, error })
     });
-    let offers = Box::new(offers).try_collect_sorted_by(on(|(_, _perf, _value)| _value,
-                                                           unsafe_cmp))?;
+    let (mut offers, mut unscoreable) = Box::new(offers).collect_sorted_partitioned_by(
+        on(|r: &RankedOffer| r.sort_key(), unsafe_cmp));
+    // the multi-socket configurations of an offer fail for the same
+    // reasons as the single one
+    unscoreable.dedup_by(|a, b| std::ptr::eq(a.offer, b.offer));
+
+    if let Some(up) = uncertainty_params {
+        let inputs = offers.iter()
+            .map(|r| Ok((db.cpu_of(r.offer)?, r.performance.sockets,
+                         r.cost() / r.risk_factor)))
+            .collect::<Result<Vec<_>>>()?;
+        let uncertainties = uncertainty::offer_uncertainties(profile, params, up, &inputs)?;
+        for (r, u) in offers.iter_mut().zip(uncertainties) {
+            r.uncertainty = Some(u);
+        }
+    }
 
     println!("{offers:#?}");
+
+    if uncertainty_params.is_some() {
+        println!("Offers whose rank may change within the uncertainty (best rank is 1):");
+        for r in offers.iter().rev() {
+            let Some(u) = &r.uncertainty else { continue };
+            if u.rank_may_change() {
+                println!("  #{} ({}..{}): {}{}, score {:.1} ({:.1}..{:.1}), \
+                          value {:.4} ({:.4}..{:.4})",
+                         u.rank, u.rank_best, u.rank_worst, socket_prefix(r.performance.sockets),
+                         r.offer.url,
+                         r.performance.score, u.score.low, u.score.high,
+                         r.sort_key(), u.value.low, u.value.high);
+            }
+        }
+    }
+
+    if ! excluded.is_empty() {
+        println!("{} used or tray offer(s) excluded by policy ({:?})",
+                 excluded.len(), policies);
+    }
+
+    if ! unscoreable.is_empty() {
+        println!("Offers that can't be scored:");
+        for u in &unscoreable {
+            println!("  {} ({}): {:#}", u.offer.url, u.offer.article_name.as_str(), u.error);
+        }
+    }
     Ok(())
 }
+
+
+/// Show how the score and value of the offers meant by `what` (a URL
+/// or CPU name) are derived, for each socket configuration.
+fn explain(db: &Database, config: &ProfileConfig, tco: bool, what: &str) -> Result<()> {
+    let params = &config.params;
+    let offers = db.offers_by_url_or_cpu(what)?;
+    if offers.is_empty() {
+        bail!("there are no offers for {what:?}")
+    }
+    for (offer, sockets) in with_socket_configurations(db, offers) {
+        let cpu = db.cpu_of(offer)?;
+        let perf = config.profile.performance_with_sockets(params, cpu, sockets)?;
+        println!("{}{} at {} for {}{}{}: {}",
+                 socket_prefix(sockets), cpu.name.as_str(), offer.shop.as_str(),
+                 format_price(offer.price),
+                 if offer.is_tray_version { ", tray" } else { "" },
+                 if offer.is_used { ", used" } else { "" },
+                 offer.url);
+        println!("  profile {:?}", config.profile);
+        match &offer.warranty_months {
+            Value::Some(m) | Value::SomeWithDoubts(m, _) => println!("  warranty: {m} months"),
+            Value::NotApplicable => println!("  warranty: none"),
+            Value::Missing => println!("  warranty: unknown"),
+        }
+        if let Some(note) = policy::cooler_note(offer, cpu) {
+            println!("  {note}");
+        }
+        for line in perf.explain(params)? {
+            println!("  {line}");
+        }
+        let Some(risk_factor) = config.offers.risk_factor(offer) else {
+            println!("  excluded from the ranking by the policy for used or tray offers");
+            continue
+        };
+        let score = if risk_factor == 1. {
+            perf.score
+        } else {
+            let score = perf.score * risk_factor;
+            println!("  risk-adjusted score (used or tray offer): {:.2} x {risk_factor:.3} = {score:.2}",
+                     perf.score);
+            score
+        };
+        let landed = landed::landed_price(&config.landed, db.shop_of(offer)?, offer.price,
+                                          sockets);
+        explain_landed_price(&config.landed, &landed);
+        let platform_chf = config.platform_chf(sockets);
+        if platform_chf > 0. {
+            println!("  multi-socket premium (board, cooler): {} x {:.0} CHF \
+                      = {platform_chf:.0} CHF",
+                     sockets - 1, config.multi_socket_premium_chf);
+        }
+        let price = landed.total_chf + platform_chf;
+        println!("  value: {score:.2} / {price:.0} CHF = {:.4} per CHF", score / price);
+        if tco {
+            let t = tco::tco(&config.tco, cpu, sockets, price)?;
+            println!("  energy: {:.0} W average ({}{} W TDP, utilisation {}, {} W platform) \
+                      over {} years = {:.0} kWh x {} CHF/kWh = {:.0} CHF",
+                     t.average_power, if sockets > 1 { format!("{sockets} x ") } else { "".into() },
+                     cpu.tdp.value()?.0, config.tco.utilisation,
+                     config.tco.platform_overhead, config.tco.service_life_years,
+                     t.energy_kwh, config.tco.electricity_chf_per_kwh, t.energy_chf);
+            println!("  value per TCO: {score:.2} / ({price:.0} + {:.0}) CHF = {:.4} per CHF",
+                     t.energy_chf, score / t.total_chf);
+        }
+    }
+    Ok(())
+}
+
+fn format_price(price: Price) -> String {
+    format!("{} {:?}", price.amount(), price.currency())
+}
+
+/// Print the parts of a landed price that are not 0.
+fn explain_landed_price(params: &LandedParams, p: &LandedPrice) {
+    println!("  landed price, delivered to {:?}:", params.destination);
+    println!("    list price: {:.2} CHF", p.list_chf);
+    if p.foreign_vat_chf != 0. {
+        println!("    foreign VAT refunded on export: {:.2} CHF", p.foreign_vat_chf);
+    }
+    match p.shipping_chf {
+        Some(chf) => println!("    shipping: {chf:.2} CHF"),
+        None => println!("    shipping: unknown, counted as 0"),
+    }
+    if p.vat_chf != 0. {
+        println!("    VAT ({:?}): {:.2} CHF", params.destination, p.vat_chf);
+    }
+    if p.duty_chf != 0. {
+        println!("    import duty: {:.2} CHF", p.duty_chf);
+    }
+    if p.clearance_fee_chf != 0. {
+        println!("    customs clearance fee: {:.2} CHF", p.clearance_fee_chf);
+    }
+    println!("    total: {:.2} CHF", p.total_chf);
+}
+
+
+/// List the offers on the Pareto front of score and price, and
+/// optionally TDP (`"tdp"` in `extra`) and delivery date
+/// (`"delivery"`), and those dominated with an offer dominating them.
+/// The scores of used and tray offers are adjusted by the policies in
+/// `config`, prices are landed prices.
+fn pareto(db: &Database, config: &ProfileConfig, extra: &[&str]) -> Result<()> {
+    let (profile, params, policies) = (config.profile, &config.params, &config.offers);
+    let mut use_tdp = false;
+    let mut use_delivery = false;
+    for e in extra {
+        match *e {
+            "tdp" => use_tdp = true,
+            "delivery" => use_delivery = true,
+            _ => bail!("unknown pareto criterion {e:?}, expecting tdp or delivery")
+        }
+    }
+    let mut offers = Vec::new();
+    let mut points = Vec::new();
+    let mut unscoreable = 0;
+    for offer in db.sold_at.rows() {
+        let cpu = db.cpu_of(offer)?;
+        let Some(risk_factor) = policies.risk_factor(offer) else { continue };
+        let Ok(perf) = profile.performance(params, cpu) else {
+            unscoreable += 1;
+            continue
+        };
+        let score = perf.score * risk_factor;
+        let price_chf =
+            landed::landed_price(&config.landed, db.shop_of(offer)?, offer.price, 1).total_chf;
+        // all lower is better; unknown values count as the worst
+        let mut point = vec![price_chf, -score];
+        if use_tdp {
+            point.push(cpu.tdp.get().map(|w| w.0 as f32).unwrap_or(f32::INFINITY));
+        }
+        if use_delivery {
+            point.push(offer.latest_delivery().map(|d| (d.unixtime() / 86400) as f32)
+                       .unwrap_or(f32::INFINITY));
+        }
+        offers.push((offer, cpu, score, price_chf));
+        points.push(point);
+    }
+    let front = pareto_front(&points);
+    let describe = |i: usize| {
+        let (offer, cpu, score, price_chf) = offers[i];
+        let mut s = format!("{price_chf:.0} CHF, score {score:.1}");
+        if use_tdp {
+            s += &format!(", {}", cpu.tdp.get().map(|w| format!("{} W", w.0))
+                          .unwrap_or("TDP unknown".into()));
+        }
+        if use_delivery {
+            s += &format!(", {}", offer.latest_delivery()
+                          .map(|d| format!("by {}.{}.{}", d.mday(), d.month(), d.year()))
+                          .unwrap_or("delivery date unknown".into()));
+        }
+        if let Some(note) = policy::cooler_note(offer, cpu) {
+            s += &format!(", {note}");
+        }
+        format!("{s}: {} ({})", cpu.name.as_str(), offer.url)
+    };
+    let mut by_price: Vec<usize> = (0..offers.len()).collect();
+    by_price.sort_by(|a, b| offers[*a].3.total_cmp(&offers[*b].3));
+    println!("Pareto-optimal offers:");
+    for i in by_price.iter().filter(|i| front[**i].is_none()) {
+        println!("  {}", describe(*i));
+    }
+    println!("Dominated offers:");
+    for i in &by_price {
+        if let Some(j) = front[*i] {
+            println!("  {}\n    dominated by {}", describe(*i), describe(j));
+        }
+    }
+    if unscoreable > 0 {
+        println!("{unscoreable} offer(s) can't be scored, see the rank command");
+    }
+    Ok(())
+}
+
+
+/// The best combinations of CPU, motherboard and memory within the
+/// budget.
+fn build(
+    db: &Database, profile: Profile, params: &ProfileParams, build_params: &BuildParams
+) -> Result<()> {
+    if db.motherboards_sold_at.is_empty() || db.memory_kits_sold_at.is_empty() {
+        println!("no motherboard or memory offers entered, can't put builds together");
+        return Ok(())
+    }
+    let builds = build::best_builds(db, profile, params, build_params)?;
+    println!("Only CPU, motherboard and memory are chosen; other parts (cooler, case, power \
+              supply, disks, ...) are not, use --reserve to set aside budget for them.");
+    if builds.is_empty() {
+        println!("no compatible build within {} CHF (with {} CHF reserved for other parts) \
+                  with at least {} GB memory",
+                 build_params.budget_chf, build_params.reserve_chf, build_params.min_memory_gb);
+    }
+    for (i, b) in builds.iter().enumerate() {
+        println!("#{}: score {:.1}, {} CHF", i + 1, b.performance.score, b.price_chf);
+        if b.risk_factor != 1. {
+            println!("    risk-adjusted score (used or tray parts): {:.1}", b.score());
+        }
+        for (name, offer) in [(b.cpu.name.as_str(), b.cpu_offer),
+                              (b.motherboard.name.as_str(), b.motherboard_offer),
+                              (b.memory.name.as_str(), b.memory_offer)]
+        {
+            println!("    {:>9}  {name} ({})", format_price(offer.price), offer.url);
+        }
+        for (shop, landed) in &b.orders {
+            println!("    order from {}: {:.0} CHF landed", shop.name.as_str(), landed.total_chf);
+        }
+        if let Some(note) = policy::cooler_note(b.cpu_offer, b.cpu) {
+            println!("    {note}");
+        }
+        println!("    memory: {} GB, {} channel(s) at {} MT/s, {:.1} GB/s",
+                 b.memory.total_gb(), b.memory_channels, b.memory_speed.0,
+                 b.performance.memory_bandwidth);
+    }
+    Ok(())
+}
+
+
+/// One line per CPU (and socket configuration, as in `rank`) with
+/// the number of its offers, their range of landed prices and the
+/// offer with the best value; with `all`, each offer below it. Used
+/// and tray offers are excluded or discounted according to the
+/// policies in `config`. CPUs that can't be scored are listed at the
+/// end with the reason.
+fn by_cpu(db: &Database, config: &ProfileConfig, all: bool) -> Result<()> {
+    let cpus = pindex_by(db.cpus.rows(), |cpu| &cpu.name)?;
+    let offers_by_cpu = mindex_by_foreign(db.sold_at.rows(), |offer| &offer.article_name,
+                                          &cpus, "SoldAt.article_name -> CPU.name")?;
+    // (cpu, score per socket configuration, offers with their landed
+    // price for one CPU and value per socket configuration, best
+    // value first)
+    let mut groups = Vec::new();
+    let mut unscoreable = Vec::new();
+    for (name, offers) in offers_by_cpu {
+        let cpu = cpus[name];
+        let offers: Vec<&SoldAt> = offers.into_values()
+            .filter(|offer| config.offers.risk_factor(offer).is_some())
+            .collect();
+        if offers.is_empty() {
+            continue
+        }
+        let mut scores = Vec::new();
+        for sockets in cpu.socket_configurations() {
+            match config.profile.performance_with_sockets(&config.params, cpu, sockets) {
+                Ok(p) => scores.push((sockets, p.score)),
+                // the multi-socket configurations fail for the same
+                // reasons as the single one
+                Err(e) => if sockets == 1 {
+                    unscoreable.push((cpu, e));
+                }
+            }
+        }
+        if scores.is_empty() {
+            continue
+        }
+        let mut priced = Vec::new();
+        for offer in &offers {
+            let risk_factor = config.offers.risk_factor(offer).expect("filtered above");
+            let shop = db.shop_of(offer)?;
+            let values: Vec<f32> = scores.iter().map(|(sockets, score)| {
+                let price_chf = landed::landed_price(&config.landed, shop, offer.price,
+                                                     *sockets).total_chf
+                    + config.platform_chf(*sockets);
+                score * risk_factor / price_chf
+            }).collect();
+            let price_chf = landed::landed_price(&config.landed, shop, offer.price, 1).total_chf;
+            priced.push((*offer, price_chf, values));
+        }
+        let best_value = |values: &[f32]| values.iter().copied().fold(0., f32::max);
+        priced.sort_by(|a, b| best_value(&b.2).total_cmp(&best_value(&a.2))
+                       .then(a.1.total_cmp(&b.1)));
+        groups.push((cpu, scores, priced));
+    }
+    let best_value = |priced: &[(&SoldAt, f32, Vec<f32>)]| {
+        priced[0].2.iter().copied().fold(0., f32::max)
+    };
+    groups.sort_by(|a, b| best_value(&b.2).total_cmp(&best_value(&a.2))
+                   .then(a.0.name.as_str().cmp(b.0.name.as_str())));
+    unscoreable.sort_by(|a, b| a.0.name.as_str().cmp(b.0.name.as_str()));
+
+    for (cpu, scores, priced) in &groups {
+        let (min, max) = priced.iter().fold((f32::INFINITY, 0f32),
+                                            |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        let range = if max - min < 0.5 {
+            format!("{min:.0} CHF")
+        } else {
+            format!("{min:.0}..{max:.0} CHF")
+        };
+        // the best value of each socket configuration
+        let configurations: Vec<String> = scores.iter().enumerate().map(|(i, (sockets, score))| {
+            let value = priced.iter().map(|p| p.2[i]).fold(0., f32::max);
+            format!("{sockets}P score {score:.1} value {value:.4}")
+        }).collect();
+        let (best, best_price, _) = &priced[0];
+        println!("{}: {} offer(s), {range}; {}; best: {best_price:.0} CHF, {}",
+                 cpu.name.as_str(), priced.len(), configurations.join(", "), best.url);
+        if all {
+            for (offer, price, values) in priced {
+                let values: Vec<String> = scores.iter().zip(values)
+                    .map(|((sockets, _), value)| format!("{sockets}P {value:.4}"))
+                    .collect();
+                println!("    {price:>5.0} CHF  value {}{}{}  {}",
+                         values.join(", "),
+                         if offer.is_tray_version { ", tray" } else { "" },
+                         if offer.is_used { ", used" } else { "" },
+                         offer.url);
+            }
+        }
+    }
+    if ! unscoreable.is_empty() {
+        println!("CPUs that can't be scored:");
+        for (cpu, error) in &unscoreable {
+            println!("  {}: {error:#}", cpu.name.as_str());
+        }
+    }
+    Ok(())
+}
+
+
+fn compare_cache(
+    db: &Database, profile: Profile, params: &ProfileParams, a: &str, b: &str
+) -> Result<()> {
+    let a = db.cpu_by_name(a)?;
+    let b = db.cpu_by_name(b)?;
+    let cmp = scoring::compare_cache(profile, params, a, b)?;
+    for (cpu, perf, without) in [(a, &cmp.a, cmp.a_without_cache),
+                                 (b, &cmp.b, cmp.b_without_cache)] {
+        let cache: Vec<String> = perf.clusters.iter().map(
+            |c| match c.cache_per_core {
+                Some(mb) => format!("{mb:.2} MB/core (factor {:.3})", c.cache_factor),
+                None => "unknown cache".into(),
+            }).collect();
+        println!("{}: score {:.2}, without cache effect {without:.2}; {}",
+                 cpu.name.as_str(), perf.score, cache.join(", "));
+    }
+    let diff = cmp.difference();
+    let by_cache = cmp.attributable_to_cache();
+    println!("difference {diff:+.2}: {by_cache:+.2} attributable to cache, {:+.2} to the rest",
+             diff - by_cache);
+    Ok(())
+}
+
+
+/// Compare the scores of all profiles with the benchmark results,
+/// using `config` for its profile and the defaults for the others.
+fn validate(db: &Database, config: &ProfileConfig) -> Result<()> {
+    if db.benchmarks.is_empty() {
+        println!("no benchmark results entered, nothing to validate");
+        return Ok(())
+    }
+    for profile in Profile::ALL {
+        let params = if profile == config.profile {
+            config.params.clone()
+        } else {
+            profile.default_params()
+        };
+        for v in calibration::validate(db, profile, &params)? {
+            let correlation = match v.correlation {
+                Some(r) => format!("{r:.3}"),
+                None => "n/a".into(),
+            };
+            println!("{} with profile {:?}: {} result(s), correlation {correlation}",
+                     v.benchmark.as_str(), v.profile, v.residuals.len());
+            for r in &v.residuals {
+                println!("    {}: measured {} {:?}, predicted {:.2}, residual {:+.1}% ({})",
+                         r.benchmark.key.cpu.as_str(), r.benchmark.result, r.benchmark.units,
+                         r.predicted, r.relative_error * 100., r.benchmark.key.source_url);
+            }
+        }
+        for (b, e) in calibration::unscoreable(db, profile, &params)? {
+            println!("{} with profile {profile:?}: can't score {}: {e:#}",
+                     b.key.benchmark.as_str(), b.key.cpu.as_str());
+        }
+    }
+    Ok(())
+}
+
+
+/// Fit the parameters of `config` to the benchmark results, and save
+/// them to `out` if given.
+fn fit(db: &Database, config: &ProfileConfig, out: Option<&str>) -> Result<()> {
+    let fit = calibration::fit(db, config.profile, &config.params)?;
+    println!("{} benchmark result(s), profile {:?}", fit.n, config.profile);
+    for (b, e) in &fit.left_out {
+        println!("    left out {} of {}: {e:#}", b.key.benchmark.as_str(), b.key.cpu.as_str());
+    }
+    for c in calibration::COEFFICIENTS {
+        let note = if fit.undetermined.contains(&c.name) { " (no influence on the data)" } else { "" };
+        println!("    {}: {:.3} -> {:.3}{note}",
+                 c.name, (c.get)(&config.params), (c.get)(&fit.params));
+    }
+    println!("R² (log scale) {:.3} -> {:.3}, typical error {:.1}%",
+             fit.r_squared_before, fit.r_squared, fit.rms_log_error.exp_m1() * 100.);
+    if ! fit.at_bound.is_empty() {
+        println!("WARNING: {} ended up at the end of the plausible range; the results don't \
+                  fit the model or are too few to determine it",
+                 fit.at_bound.join(", "));
+    }
+    if let Some(out) = out {
+        if ! fit.at_bound.is_empty() {
+            bail!("not writing {out:?}, as coefficients ended up at a bound")
+        }
+        ProfileConfig { params: fit.params, ..config.clone() }.save(out.as_ref())?;
+        println!("written to {out:?}, use with --params {out:?}");
+    }
+    Ok(())
+}
+
+
+fn main() -> Result<()> {
+    let mut positional = Vec::new();
+    let mut profile = None;
+    let mut params_file = None;
+    let mut parallel_fraction = None;
+    let mut tco = false;
+    let mut uncertainty_params = None;
+    let mut min_memory_gb = 32;
+    let mut reserve_chf = 0;
+    let mut tco_options = Vec::new();
+    let mut used_policy = None;
+    let mut tray_policy = None;
+    let mut destination = None;
+    let mut chf_per_eur = None;
+    let mut multi_socket_premium = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--profile" => {
+                let Some(name) = args.next() else { bail!("--profile needs an argument") };
+                profile = Some(Profile::try_from(&*name)?);
+            }
+            "--params" => {
+                let Some(path) = args.next() else { bail!("--params needs an argument") };
+                params_file = Some(path);
+            }
+            "--parallel-fraction" => {
+                let Some(f) = args.next() else { bail!("--parallel-fraction needs an argument") };
+                let f: f32 = f.parse().context("parsing --parallel-fraction")?;
+                if ! (0. ..=1.).contains(&f) {
+                    bail!("--parallel-fraction must be between 0 and 1, got {f}")
+                }
+                parallel_fraction = Some(f);
+            }
+            "--tco" => tco = true,
+            "--destination" => {
+                let Some(v) = args.next() else { bail!("--destination needs an argument") };
+                destination = Some(Country::try_from(&*v)?);
+            }
+            "--chf-per-eur" => {
+                let Some(v) = args.next() else { bail!("--chf-per-eur needs an argument") };
+                let v: f32 = v.parse().context("parsing --chf-per-eur")?;
+                if v <= 0. {
+                    bail!("--chf-per-eur must be positive, got {v}")
+                }
+                chf_per_eur = Some(v);
+            }
+            "--multi-socket-premium" => {
+                let Some(v) = args.next() else { bail!("--multi-socket-premium needs an argument") };
+                let v: f32 = v.parse().context("parsing --multi-socket-premium")?;
+                if v < 0. {
+                    bail!("--multi-socket-premium must not be negative, got {v}")
+                }
+                multi_socket_premium = Some(v);
+            }
+            "--used" | "--tray" => {
+                let Some(v) = args.next() else { bail!("{arg} needs an argument") };
+                let policy = OfferPolicy::try_from(&*v).with_context(|| format!("parsing {arg}"))?;
+                if arg == "--used" { used_policy = Some(policy) } else { tray_policy = Some(policy) }
+            }
+            "--min-memory" | "--reserve" => {
+                let Some(v) = args.next() else { bail!("{arg} needs an argument") };
+                let v: u32 = v.parse().with_context(|| format!("parsing {arg}"))?;
+                if arg == "--min-memory" { min_memory_gb = v } else { reserve_chf = v }
+            }
+            "--uncertainty" => uncertainty_params = Some(UncertaintyParams::default()),
+            "--electricity-price" | "--utilisation" | "--service-life" | "--platform-overhead" => {
+                let Some(v) = args.next() else { bail!("{arg} needs an argument") };
+                let v: f32 = v.parse().with_context(|| format!("parsing {arg}"))?;
+                if v < 0. {
+                    bail!("{arg} must not be negative, got {v}")
+                }
+                if arg == "--utilisation" && v > 1. {
+                    bail!("--utilisation must be between 0 and 1, got {v}")
+                }
+                tco = true;
+                tco_options.push((arg, v));
+            }
+            _ if arg.starts_with("--") => bail!("unknown option {arg:?}"),
+            _ => positional.push(arg)
+        }
+    }
+
+    let mut config = if let Some(path) = &params_file {
+        let config = ProfileConfig::load(path.as_ref())?;
+        if let Some(profile) = profile {
+            if profile != config.profile {
+                bail!("--profile {profile:?} contradicts profile {:?} in {path:?}",
+                      config.profile)
+            }
+        }
+        config
+    } else {
+        ProfileConfig::new(profile.unwrap_or(Profile::Compilation))
+    };
+    if let Some(f) = parallel_fraction {
+        config.params.parallel_fraction = f;
+    }
+    for (option, v) in tco_options {
+        let p = &mut config.tco;
+        match &*option {
+            "--electricity-price" => p.electricity_chf_per_kwh = v,
+            "--utilisation" => p.utilisation = v,
+            "--service-life" => p.service_life_years = v,
+            "--platform-overhead" => p.platform_overhead = v,
+            _ => unreachable!()
+        }
+    }
+    if let Some(policy) = used_policy {
+        config.offers.used = policy;
+    }
+    if let Some(policy) = tray_policy {
+        config.offers.tray = policy;
+    }
+    if let Some(country) = destination {
+        config.landed.destination = country;
+    }
+    if let Some(rate) = chf_per_eur {
+        config.landed.chf_per_eur = rate;
+    }
+    if let Some(chf) = multi_socket_premium {
+        config.multi_socket_premium_chf = chf;
+    }
+    let (profile, params) = (config.profile, &config.params);
+    let db = database()?;
+
+    let positional: Vec<&str> = positional.iter().map(|s| s.as_str()).collect();
+    if positional == ["lint"] {
+        return lint(&db)
+    }
+    if positional == ["params"] {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(())
+    }
+
+    let report = db.validate();
+    report.print();
+    report.into_result()?;
+
+    match &*positional {
+        [] | ["rank"] => rank(&db, &config, tco, uncertainty_params.as_ref()),
+        ["compare-cache", a, b] => compare_cache(&db, profile, params, a, b),
+        ["explain", what] => explain(&db, &config, tco, what),
+        ["pareto", extra @ ..] => pareto(&db, &config, extra),
+        ["by-cpu"] => by_cpu(&db, &config, false),
+        ["by-cpu", "all"] => by_cpu(&db, &config, true),
+        ["build", budget, top_n @ ..] if top_n.len() <= 1 => {
+            let budget_chf = budget.parse().with_context(|| format!("parsing budget {budget:?}"))?;
+            let top_n = match top_n {
+                [n] => n.parse().with_context(|| format!("parsing number of builds {n:?}"))?,
+                _ => 5,
+            };
+            build(&db, profile, params,
+                  &BuildParams { budget_chf, reserve_chf, min_memory_gb, top_n,
+                                 offer_policies: config.offers.clone(),
+                                 landed: config.landed.clone() })
+        }
+        ["validate"] => validate(&db, &config),
+        ["fit"] => fit(&db, &config, None),
+        ["fit", out] => fit(&db, &config, Some(out)),
+        _ => bail!("unknown command or wrong number of arguments: {positional:?}; \
+                    expecting rank, lint, params, explain <url-or-cpu>, \
+                    pareto [tdp] [delivery], by-cpu [all], build <budget> [<n>], validate, \
+                    fit [<file.json>], or compare-cache <cpu> <cpu>")
+    }
+}
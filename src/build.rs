@@ -0,0 +1,292 @@
+//! Choosing a combination of CPU, motherboard and memory within a
+//! budget.
+
+use anyhow::Result;
+
+use crate::{database::Database,
+            landed::{LandedParams, LandedPrice, landed_order},
+            policy::OfferPolicies,
+            scoring::{Profile, ProfileParams, Performance},
+            types::{CPU, SoldAt, Motherboard, MemoryKit, MTperSec, Shop},
+            value::Value};
+
+
+#[derive(Debug, Clone)]
+pub struct BuildParams {
+    pub budget_chf: u32,
+    /// Amount of the budget set aside for the other parts (case,
+    /// power supply, disks, ...)
+    pub reserve_chf: u32,
+    pub min_memory_gb: u32,
+    /// How many builds to return
+    pub top_n: usize,
+    /// Which used and tray parts to leave out or discount
+    pub offer_policies: OfferPolicies,
+    /// For the landed prices of the parts
+    pub landed: LandedParams,
+}
+
+#[derive(Debug)]
+pub struct Build<'t> {
+    pub cpu_offer: &'t SoldAt,
+    pub cpu: &'t CPU,
+    pub motherboard_offer: &'t SoldAt,
+    pub motherboard: &'t Motherboard,
+    pub memory_offer: &'t SoldAt,
+    pub memory: &'t MemoryKit,
+    /// Memory channels actually used: limited by the CPU, the board
+    /// and the number of modules
+    pub memory_channels: u8,
+    /// Limited by the CPU and the memory
+    pub memory_speed: MTperSec,
+    /// Of the CPU with the above memory configuration
+    pub performance: Performance,
+    /// Product of the risk factors of the three offers
+    pub risk_factor: f32,
+    /// One order per shop the parts are bought from, so that each
+    /// shop's shipping and customs clearance are paid once
+    pub orders: Vec<(&'t Shop, LandedPrice)>,
+    /// Sum of the landed prices of the orders
+    pub price_chf: u32,
+}
+
+impl Build<'_> {
+    /// The score adjusted for the risk of used and tray parts
+    pub fn score(&self) -> f32 {
+        self.performance.score * self.risk_factor
+    }
+}
+
+/// Why `cpu`, `board` and `memory` can't be combined; empty if they
+/// can. Unknown values are assumed to be compatible.
+pub fn incompatibilities(cpu: &CPU, board: &Motherboard, memory: &MemoryKit) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(socket) = cpu.cpu_socket.get() {
+        if *socket != board.cpu_socket {
+            problems.push(format!("CPU socket {socket:?} does not fit board socket {:?}",
+                                  board.cpu_socket));
+        }
+    }
+    if let Some(t) = cpu.system_memory_type.get() {
+        if *t != board.memory_type {
+            problems.push(format!("CPU supports {t:?}, board {:?}", board.memory_type));
+        }
+    }
+    if memory.memory_type != board.memory_type {
+        problems.push(format!("memory is {:?}, board takes {:?}",
+                              memory.memory_type, board.memory_type));
+    }
+    for (what, subtype) in [("CPU", &cpu.system_memory_subtype),
+                            ("board", &board.memory_subtype)] {
+        if let (Some(a), Some(b)) = (subtype.get(), memory.memory_subtype.get()) {
+            if a != b {
+                problems.push(format!("memory is {b:?}, {what} takes {a:?}"));
+            }
+        }
+    }
+    if memory.modules > board.memory_slots {
+        problems.push(format!("{} memory modules, but only {} slots",
+                              memory.modules, board.memory_slots));
+    }
+    if let Some(max) = board.max_memory_gb.get() {
+        if memory.total_gb() > *max as u32 {
+            problems.push(format!("{} GB memory, but board supports at most {max} GB",
+                                  memory.total_gb()));
+        }
+    }
+    problems
+}
+
+/// The landed prices of buying `offers`, one order per shop.
+fn orders<'t>(
+    db: &'t Database, params: &LandedParams, offers: &[&SoldAt]
+) -> Result<Vec<(&'t Shop, LandedPrice)>> {
+    let mut items_by_shop: Vec<(&Shop, Vec<_>)> = Vec::new();
+    for offer in offers {
+        let shop = db.shop_of(offer)?;
+        match items_by_shop.iter_mut().find(|(s, _)| s.name == shop.name) {
+            Some((_, items)) => items.push((offer.price, 1)),
+            None => items_by_shop.push((shop, vec![(offer.price, 1)])),
+        }
+    }
+    Ok(items_by_shop.into_iter()
+       .map(|(shop, items)| (shop, landed_order(params, shop, &items)))
+       .collect())
+}
+
+/// The best builds by (risk-adjusted) score, then by lower price,
+/// that fit `build_params`; builds whose CPU can't be scored are left
+/// out.
+pub fn best_builds<'t>(
+    db: &'t Database, profile: Profile, params: &ProfileParams, build_params: &BuildParams
+) -> Result<Vec<Build<'t>>> {
+    let budget = build_params.budget_chf.saturating_sub(build_params.reserve_chf);
+    let policies = &build_params.offer_policies;
+    let board_offers = db.motherboards_sold_at.rows().iter()
+        .filter_map(|o| Some((o, policies.risk_factor(o)?)))
+        .map(|(o, risk)| Ok((o, db.motherboard_of(o)?, risk)))
+        .collect::<Result<Vec<_>>>()?;
+    let memory_offers = db.memory_kits_sold_at.rows().iter()
+        .filter_map(|o| Some((o, policies.risk_factor(o)?)))
+        .map(|(o, risk)| Ok((o, db.memory_kit_of(o)?, risk)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut builds = Vec::new();
+    for cpu_offer in db.sold_at.rows() {
+        let Some(cpu_risk) = policies.risk_factor(cpu_offer) else { continue };
+        let cpu = db.cpu_of(cpu_offer)?;
+        for (motherboard_offer, motherboard, motherboard_risk) in &board_offers {
+            for (memory_offer, memory, memory_risk) in &memory_offers {
+                if memory.total_gb() < build_params.min_memory_gb
+                    || ! incompatibilities(cpu, motherboard, memory).is_empty()
+                {
+                    continue
+                }
+                let orders = orders(db, &build_params.landed,
+                                    &[cpu_offer, motherboard_offer, memory_offer])?;
+                let price_chf =
+                    orders.iter().map(|(_, landed)| landed.total_chf).sum::<f32>().round() as u32;
+                if price_chf > budget {
+                    continue
+                }
+                let Some(cpu_channels) = cpu.memory_channels.get() else { continue };
+                let memory_channels =
+                    (*cpu_channels).min(motherboard.memory_channels).min(memory.modules);
+                let memory_speed = MTperSec(match cpu.system_memory_specification.get() {
+                    Some(s) => s.0.min(memory.speed.0),
+                    None => memory.speed.0,
+                });
+                let mut configured = cpu.clone();
+                configured.memory_channels = Value::Some(memory_channels);
+                configured.system_memory_specification = Value::Some(memory_speed.clone());
+                let Ok(performance) = profile.performance(params, &configured) else { continue };
+                builds.push(Build {
+                    cpu_offer, cpu, motherboard_offer, motherboard, memory_offer, memory,
+                    memory_channels, memory_speed, performance,
+                    risk_factor: cpu_risk * motherboard_risk * memory_risk,
+                    orders,
+                    price_chf,
+                });
+            }
+        }
+    }
+    builds.sort_by(|a, b| b.score().total_cmp(&a.score())
+                   .then(a.price_chf.cmp(&b.price_chf)));
+    builds.truncate(build_params.top_n);
+    Ok(builds)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{database::Data,
+                types::{Country, Currency, CPUSocket, MemorySubtype, MemoryType, Price, Shipping,
+                        Shop}};
+    use super::*;
+
+    fn board() -> Motherboard {
+        Motherboard {
+            name: "SP3 board".into(),
+            url: "".into(),
+            cpu_socket: CPUSocket::SP3,
+            memory_type: MemoryType::DDR4,
+            memory_subtype: MemorySubtype::RDIMM.into(),
+            memory_slots: 8,
+            memory_channels: 8,
+            max_memory_gb: 1024.into(),
+        }
+    }
+
+    fn kit(modules: u8, module_gb: u16) -> MemoryKit {
+        MemoryKit {
+            name: format!("{modules} x {module_gb} GB").as_str().into(),
+            url: "".into(),
+            memory_type: MemoryType::DDR4,
+            memory_subtype: MemorySubtype::RDIMM.into(),
+            modules,
+            module_gb,
+            speed: MTperSec(3200),
+        }
+    }
+
+    fn offer(article_name: &str, chf: u16) -> SoldAt {
+        SoldAt {
+            article_name: article_name.into(),
+            desc: "".into(),
+            shop: "Test".into(),
+            url: format!("https://example.com/{article_name}"),
+            price: Price::CHF(chf),
+            is_tray_version: false,
+            is_used: false,
+            warranty_months: Value::Missing,
+            delivered: "".into(),
+            retrieved: Value::Missing,
+        }
+    }
+
+    #[test]
+    fn t_incompatibilities() {
+        let cpu = CPU::test_epyc_7543p();
+        assert!(incompatibilities(&cpu, &board(), &kit(8, 32)).is_empty());
+
+        let am5 = Motherboard { cpu_socket: CPUSocket::AM5, memory_type: MemoryType::DDR5,
+                                ..board() };
+        assert_eq!(incompatibilities(&cpu, &am5, &kit(8, 32)).len(), 3);
+
+        let udimm = MemoryKit { memory_subtype: MemorySubtype::UDIMM.into(), ..kit(8, 32) };
+        assert_eq!(incompatibilities(&cpu, &board(), &udimm).len(), 2);
+        // unknown values are assumed to fit
+        let unknown = MemoryKit { memory_subtype: Value::Missing, ..kit(8, 32) };
+        assert!(incompatibilities(&cpu, &board(), &unknown).is_empty());
+
+        assert_eq!(incompatibilities(&cpu, &board(), &kit(16, 16)),
+                   ["16 memory modules, but only 8 slots"]);
+        assert_eq!(incompatibilities(&cpu, &board(), &kit(8, 256)),
+                   ["2048 GB memory, but board supports at most 1024 GB"]);
+    }
+
+    #[test]
+    fn t_best_builds() {
+        let cpu = CPU::test_epyc_7543p();
+        let db = Database::new(Data {
+            shops: vec![Shop {
+                name: "Test".into(),
+                url_domain: "example.com".into(),
+                country: Country::CH,
+                currency: Currency::CHF,
+                shipping: Shipping::Flat(10).into(),
+                prices_include_vat: true,
+            }],
+            sold_at: vec![offer(cpu.name.as_str(), 1000)],
+            cpus: vec![cpu],
+            benchmarks: vec![],
+            motherboards: vec![board()],
+            motherboards_sold_at: vec![offer("SP3 board", 500)],
+            memory_kits: vec![kit(4, 16), kit(8, 16)],
+            memory_kits_sold_at: vec![offer("4 x 16 GB", 200), offer("8 x 16 GB", 400)],
+            lint_suppressions: vec![],
+        }).unwrap();
+        let profile = Profile::AI;
+        let params = profile.default_params();
+        let build_params = |budget_chf, reserve_chf, min_memory_gb| BuildParams {
+            budget_chf, reserve_chf, min_memory_gb, top_n: 5,
+            offer_policies: OfferPolicies::default(),
+            landed: LandedParams::default(),
+        };
+
+        // all eight channels give the better score; the parts come in
+        // one order, shipped once
+        let builds = best_builds(&db, profile, &params, &build_params(2000, 0, 32)).unwrap();
+        let summary: Vec<_> = builds.iter().map(|b| (b.memory_channels, b.price_chf)).collect();
+        assert_eq!(summary, [(8, 1910), (4, 1710)]);
+        assert_eq!(builds[0].orders.len(), 1);
+        assert!(builds[0].score() > builds[1].score());
+        assert_eq!(builds[1].performance.memory_bandwidth * 2.,
+                   builds[0].performance.memory_bandwidth);
+
+        let builds = best_builds(&db, profile, &params, &build_params(2000, 200, 32)).unwrap();
+        assert_eq!(builds.iter().map(|b| b.price_chf).collect::<Vec<_>>(), [1710]);
+        let builds = best_builds(&db, profile, &params, &build_params(2000, 0, 256)).unwrap();
+        assert!(builds.is_empty());
+    }
+}
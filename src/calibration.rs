@@ -0,0 +1,439 @@
+//! Comparing the performance estimates of the scoring profiles with
+//! measured benchmark results, and fitting the profile parameters to
+//! them.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+
+use crate::{database::Database,
+            scoring::{Profile, ProfileParams},
+            stats::{pearson, log_scale, mean, least_squares, sum_of_squares},
+            types::{Benchmark, BenchmarkName}};
+
+
+#[derive(Debug)]
+pub struct Residual<'t> {
+    pub benchmark: &'t Benchmark,
+    /// The score, scaled to the benchmark, in its units
+    pub predicted: f32,
+    /// Measured speed / predicted speed - 1, i.e. positive if the CPU
+    /// is faster than predicted
+    pub relative_error: f32,
+}
+
+/// How well one profile predicts the results of one benchmark.
+#[derive(Debug)]
+pub struct BenchmarkValidation<'t> {
+    pub benchmark: BenchmarkName,
+    pub profile: Profile,
+    /// Between scores and measured speeds; None with less than two
+    /// results
+    pub correlation: Option<f32>,
+    /// Benchmark speed per score point
+    pub scale: f32,
+    pub residuals: Vec<Residual<'t>>,
+}
+
+/// The benchmark results per benchmark name, without those of CPUs
+/// that `profile` can't score (see `unscoreable`).
+fn by_name<'t>(
+    db: &'t Database, profile: Profile, params: &ProfileParams
+) -> Result<BTreeMap<&'t str, Vec<&'t Benchmark>>> {
+    let mut by_name: BTreeMap<&str, Vec<&Benchmark>> = BTreeMap::new();
+    for b in db.benchmarks.rows() {
+        if profile.performance(params, db.cpu_of_benchmark(b)?).is_ok() {
+            by_name.entry(b.key.benchmark.as_str()).or_default().push(b);
+        }
+    }
+    Ok(by_name)
+}
+
+/// The benchmark results left out because `profile` can't score their
+/// CPU (e.g. for a missing memory bandwidth), with the reason.
+pub fn unscoreable<'t>(
+    db: &'t Database, profile: Profile, params: &ProfileParams
+) -> Result<Vec<(&'t Benchmark, anyhow::Error)>> {
+    let mut unscoreable = Vec::new();
+    for b in db.benchmarks.rows() {
+        if let Err(e) = profile.performance(params, db.cpu_of_benchmark(b)?) {
+            unscoreable.push((b, e));
+        }
+    }
+    Ok(unscoreable)
+}
+
+/// Compare the scores of `profile` with all benchmark results, per
+/// benchmark name (results of different benchmarks are not
+/// comparable).
+pub fn validate<'t>(
+    db: &'t Database, profile: Profile, params: &ProfileParams
+) -> Result<Vec<BenchmarkValidation<'t>>> {
+    let mut validations = Vec::new();
+    for (name, results) in by_name(db, profile, params)? {
+        let mut scores = Vec::new();
+        let mut measured = Vec::new();
+        for b in &results {
+            scores.push(profile.performance(params, db.cpu_of_benchmark(b)?)?.score);
+            measured.push(b.speed());
+        }
+        let scale = log_scale(&scores, &measured);
+        let residuals = results.iter().zip(scores.iter().zip(&measured)).map(
+            |(benchmark, (score, m))| {
+                let speed = score * scale;
+                let predicted = if benchmark.units.lower_is_better() { 1. / speed } else { speed };
+                Residual { benchmark, predicted, relative_error: m / speed - 1. }
+            }).collect();
+        validations.push(BenchmarkValidation {
+            benchmark: name.into(),
+            profile,
+            correlation: pearson(&scores, &measured),
+            scale,
+            residuals,
+        });
+    }
+    Ok(validations)
+}
+
+
+/// A parameter of `ProfileParams` that `fit` adjusts.
+pub struct Coefficient {
+    pub name: &'static str,
+    pub get: fn(&ProfileParams) -> f32,
+    pub set: fn(&mut ProfileParams, f32),
+    /// Plausible range
+    pub min: f32,
+    pub max: f32,
+}
+
+pub const COEFFICIENTS: &[Coefficient] = &[
+    Coefficient {
+        name: "smt_factor",
+        get: |p| p.smt_factor,
+        set: |p, v| p.smt_factor = v,
+        min: 0.,
+        max: 1.,
+    },
+    Coefficient {
+        name: "doubling_period_years",
+        get: |p| p.doubling_period_years,
+        set: |p, v| p.doubling_period_years = v,
+        min: 1.,
+        max: 20.,
+    },
+    Coefficient {
+        name: "parallel_fraction",
+        get: |p| p.parallel_fraction,
+        set: |p, v| p.parallel_fraction = v,
+        min: 0.,
+        max: 1.,
+    },
+    Coefficient {
+        name: "bandwidth_elasticity",
+        get: |p| p.bandwidth_elasticity,
+        set: |p, v| p.bandwidth_elasticity = v,
+        min: 0.,
+        max: 3.,
+    },
+    Coefficient {
+        name: "cache_weight",
+        get: |p| p.cache_weight,
+        set: |p, v| p.cache_weight = v,
+        min: 0.,
+        max: 1.,
+    },
+];
+
+/// How many benchmark results `fit` needs per coefficient that the
+/// data depends on.
+pub const RESULTS_PER_COEFFICIENT: usize = 3;
+
+#[derive(Debug)]
+pub struct Fit<'t> {
+    pub params: ProfileParams,
+    /// Number of benchmark results used
+    pub n: usize,
+    /// Results left out because their CPU can't be scored with the
+    /// starting parameters, or with one of the coefficients at a
+    /// bound, with the reason
+    pub left_out: Vec<(&'t Benchmark, anyhow::Error)>,
+    /// Share of the variance of the log results (within each
+    /// benchmark) explained by the model, before and after fitting
+    pub r_squared_before: f64,
+    pub r_squared: f64,
+    /// Root mean square of the log residuals; roughly the typical
+    /// relative error
+    pub rms_log_error: f64,
+    /// Names of the coefficients that the data doesn't depend on
+    /// (e.g. `doubling_period_years` if all CPUs are in the `IPC`
+    /// table), left unchanged
+    pub undetermined: Vec<&'static str>,
+    /// Names of the coefficients the fit moved to the end of their
+    /// range; their values are likely wrong (too few or unsuitable
+    /// results)
+    pub at_bound: Vec<&'static str>,
+}
+
+/// The benchmark results per benchmark name that `fit` uses: those
+/// of CPUs that `profile` can score with `params`, and with each
+/// coefficient at either bound, so that the set doesn't change while
+/// fitting (e.g. a CPU without max boost clock can only be scored
+/// with a `parallel_fraction` of 1). Also returns the others, with
+/// the reason.
+#[allow(clippy::type_complexity)]
+fn fit_results<'t>(
+    db: &'t Database, profile: Profile, params: &ProfileParams
+) -> Result<(BTreeMap<&'t str, Vec<&'t Benchmark>>, Vec<(&'t Benchmark, anyhow::Error)>)> {
+    let mut variants = vec![params.clone()];
+    for c in COEFFICIENTS {
+        for v in [c.min, c.max] {
+            let mut p = params.clone();
+            (c.set)(&mut p, v);
+            variants.push(p);
+        }
+    }
+    let mut by_name: BTreeMap<&str, Vec<&Benchmark>> = BTreeMap::new();
+    let mut left_out = Vec::new();
+    'results: for b in db.benchmarks.rows() {
+        let cpu = db.cpu_of_benchmark(b)?;
+        for p in &variants {
+            if let Err(e) = profile.performance(p, cpu) {
+                left_out.push((b, e));
+                continue 'results
+            }
+        }
+        by_name.entry(b.key.benchmark.as_str()).or_default().push(b);
+    }
+    Ok((by_name, left_out))
+}
+
+/// Log residuals of `results`, with each benchmark scaled separately
+/// (see `log_scale`).
+fn log_residuals(
+    db: &Database, profile: Profile, params: &ProfileParams,
+    results: &BTreeMap<&str, Vec<&Benchmark>>
+) -> Result<Vec<f64>> {
+    let mut residuals = Vec::new();
+    for results in results.values() {
+        let mut logs = Vec::new();
+        for b in results {
+            let score = profile.performance(params, db.cpu_of_benchmark(b)?)?.score;
+            logs.push((b.speed() / score).ln());
+        }
+        let m = mean(&logs);
+        residuals.extend(logs.iter().map(|l| (l - m) as f64));
+    }
+    Ok(residuals)
+}
+
+/// Least squares fit of the `COEFFICIENTS` of `params` to the
+/// benchmark results of CPUs that can be scored (see `fit_results`),
+/// on a log scale (i.e. relative errors count). Needs
+/// `RESULTS_PER_COEFFICIENT` results per coefficient the data depends
+/// on.
+pub fn fit<'t>(db: &'t Database, profile: Profile, params: &ProfileParams) -> Result<Fit<'t>> {
+    let (results, left_out) = fit_results(db, profile, params)?;
+    let n = results.values().map(|results| results.len()).sum();
+    let with = |p: &[f64]| {
+        let mut params = params.clone();
+        for (c, v) in COEFFICIENTS.iter().zip(p) {
+            (c.set)(&mut params, *v as f32);
+        }
+        params
+    };
+    let p0: Vec<f64> = COEFFICIENTS.iter().map(|c| (c.get)(params) as f64).collect();
+    let bounds: Vec<(f64, f64)> = COEFFICIENTS.iter().map(|c| (c.min as f64, c.max as f64)).collect();
+    let residuals = |p: &[f64]| log_residuals(db, profile, &with(p), &results);
+
+    // Total sum of squares: of the log results around their mean
+    // within each benchmark
+    let mut ss_total = 0.;
+    for results in results.values() {
+        let logs: Vec<f32> = results.iter().map(|b| b.speed().ln()).collect();
+        let m = mean(&logs);
+        ss_total += logs.iter().map(|l| ((l - m) as f64).powi(2)).sum::<f64>();
+    }
+    let r_squared = |ssr: f64| if ss_total > 0. { 1. - ssr / ss_total } else { f64::NAN };
+
+    let r0 = residuals(&p0)?;
+    let mut undetermined = Vec::new();
+    for (i, c) in COEFFICIENTS.iter().enumerate() {
+        let mut p = p0.clone();
+        p[i] = if p[i] < (c.min + c.max) as f64 / 2. { c.max as f64 } else { c.min as f64 };
+        if residuals(&p)? == r0 {
+            undetermined.push(c.name);
+        }
+    }
+    let determined = COEFFICIENTS.len() - undetermined.len();
+    if n < determined * RESULTS_PER_COEFFICIENT {
+        bail!("need at least {} benchmark results to fit {determined} coefficients, have {n}",
+              determined * RESULTS_PER_COEFFICIENT)
+    }
+
+    let (p, ssr) = least_squares(&p0, &bounds, residuals)?;
+    let at_bound = COEFFICIENTS.iter().zip(p.iter().zip(&p0))
+        .filter(|(c, (v, v0))| v != v0 && (**v <= c.min as f64 || **v >= c.max as f64))
+        .map(|(c, _)| c.name)
+        .collect();
+    Ok(Fit {
+        params: with(&p),
+        n,
+        left_out,
+        r_squared_before: r_squared(sum_of_squares(&r0)),
+        r_squared: r_squared(ssr),
+        rms_log_error: (ssr / n as f64).sqrt(),
+        undetermined,
+        at_bound,
+    })
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{database::Data, types::{CPU, BenchmarkKey, BenchmarkUnit}, value::Value};
+    use super::*;
+
+    fn cpu(name: &str, cores: u8, threads: u8) -> CPU {
+        CPU { name: name.into(), cores: cores.into(), threads: threads.into(),
+              ..CPU::test_epyc_7543p() }
+    }
+
+    fn cpus() -> Vec<CPU> {
+        vec![cpu("a", 8, 16), cpu("b", 16, 32), cpu("c", 24, 48), cpu("d", 32, 64),
+             cpu("e", 64, 128), cpu("f", 16, 16), cpu("g", 48, 96)]
+    }
+
+    /// Results of a benchmark in seconds for `cpus`, `f(i, score)` for
+    /// the i-th CPU's score with `params`.
+    fn results(
+        cpus: &[CPU], profile: Profile, params: &ProfileParams, f: impl Fn(usize, f32) -> f32
+    ) -> Vec<Benchmark> {
+        cpus.iter().enumerate().map(|(i, cpu)| Benchmark {
+            key: BenchmarkKey {
+                cpu: cpu.name.clone(),
+                benchmark: "build".into(),
+                source_url: "https://example.com".into(),
+            },
+            result: f(i, profile.performance(params, cpu).unwrap().score),
+            units: BenchmarkUnit::Seconds,
+            date: Value::Missing,
+        }).collect()
+    }
+
+    fn database(cpus: Vec<CPU>, benchmarks: Vec<Benchmark>) -> Database {
+        Database::new(Data {
+            shops: vec![], cpus, sold_at: vec![], benchmarks, motherboards: vec![],
+            motherboards_sold_at: vec![], memory_kits: vec![], memory_kits_sold_at: vec![],
+            lint_suppressions: vec![],
+        }).unwrap()
+    }
+
+    #[test]
+    fn t_validate() {
+        let profile = Profile::Compilation;
+        let params = profile.default_params();
+        let exact = results(&cpus(), profile, &params, |_, score| 1000. / score);
+        let db = database(cpus(), exact);
+        let v = validate(&db, profile, &params).unwrap();
+        assert_eq!(v.len(), 1);
+        assert!((v[0].correlation.unwrap() - 1.).abs() < 1e-5);
+        for r in &v[0].residuals {
+            assert!(r.relative_error.abs() < 1e-5);
+            assert!((r.predicted - r.benchmark.result).abs() / r.predicted < 1e-5);
+        }
+
+        // "b" takes 25% longer than predicted, i.e. is 20% slower; as
+        // the scale is fitted to all CPUs, the others appear slightly
+        // faster
+        let slow_b = results(&cpus(), profile, &params,
+                             |i, score| if i == 1 { 1250. / score } else { 1000. / score });
+        let db = database(cpus(), slow_b);
+        let v = validate(&db, profile, &params).unwrap();
+        let correlation = v[0].correlation.unwrap();
+        assert!(0.95 < correlation && correlation < 1., "{correlation}");
+        let k = 0.8f32.powf(-1. / 7.);
+        for r in &v[0].residuals {
+            let expected = if r.benchmark.key.cpu.as_str() == "b" { 0.8 * k - 1. } else { k - 1. };
+            assert!((r.relative_error - expected).abs() < 1e-4,
+                    "{}: {} != {expected}", r.benchmark.key.cpu.as_str(), r.relative_error);
+        }
+    }
+
+    /// Enough CPUs for `fit`: with and without SMT, and with 4 or 8
+    /// memory channels.
+    fn many_cpus() -> Vec<CPU> {
+        let mut cpus = Vec::new();
+        for cores in [8, 12, 16, 24, 32, 48, 64] {
+            for (smt, channels) in [(true, 8), (false, 4)] {
+                let threads = if smt { 2 * cores } else { cores };
+                cpus.push(CPU { memory_channels: channels.into(),
+                                ..cpu(&format!("{cores}/{threads}"), cores, threads) });
+            }
+        }
+        cpus
+    }
+
+    #[test]
+    fn t_fit() {
+        let profile = Profile::Compilation;
+        let params = profile.default_params();
+        let truth = ProfileParams { smt_factor: 0.5, ..params.clone() };
+        let measured = |truth: &ProfileParams| {
+            database(many_cpus(), results(&many_cpus(), profile, truth, |_, score| 1000. / score))
+        };
+        let db = measured(&truth);
+        let fitted = fit(&db, profile, &params).unwrap();
+        assert_eq!(fitted.n, 14);
+        assert!(fitted.left_out.is_empty());
+        assert!(fitted.r_squared_before < 0.999);
+        assert!(fitted.r_squared > 0.999, "{}", fitted.r_squared);
+        assert!((fitted.params.smt_factor - 0.5).abs() < 0.05, "{}", fitted.params.smt_factor);
+        // all CPUs are Zen 3, thus in the `IPC` table
+        assert_eq!(fitted.undetermined, ["doubling_period_years"]);
+        assert!(fitted.at_bound.is_empty(), "{:?}", fitted.at_bound);
+
+        // SMT giving more than a whole core can't be fitted
+        let db = measured(&ProfileParams { smt_factor: 1.5, ..params.clone() });
+        assert_eq!(fit(&db, profile, &params).unwrap().at_bound, ["smt_factor"]);
+
+        // 4 coefficients need 12 results
+        let few = results(&cpus(), profile, &params, |_, score| 1000. / score);
+        let e = fit(&database(cpus(), few), profile, &params).unwrap_err();
+        assert_eq!(e.to_string(), "need at least 12 benchmark results to fit 4 coefficients, \
+                                   have 7");
+    }
+
+    #[test]
+    fn t_fit_results() {
+        // without max boost clock, a CPU can be scored with a parallel
+        // fraction of 1 only; it is left out of the fit, rather than
+        // dropping out while fitting
+        let profile = Profile::Compilation;
+        let params = ProfileParams { parallel_fraction: 1., ..profile.default_params() };
+        let mut cpus = many_cpus();
+        let benchmarks = results(&cpus, profile, &params, |_, score| 1000. / score);
+        cpus[0].max_boost_clock = Value::Missing;
+        let db = database(cpus, benchmarks);
+        assert!(unscoreable(&db, profile, &params).unwrap().is_empty());
+        let fitted = fit(&db, profile, &params).unwrap();
+        assert_eq!(fitted.n, 13);
+        assert_eq!(fitted.left_out.len(), 1);
+        assert_eq!(fitted.left_out[0].0.key.cpu.as_str(), "8/16");
+        assert_eq!(fitted.left_out[0].1.to_string(), "missing max_boost_clock");
+    }
+
+    #[test]
+    fn t_unscoreable() {
+        let profile = Profile::Compilation;
+        let params = profile.default_params();
+        let mut cpus = cpus();
+        let benchmarks = results(&cpus, profile, &params, |_, score| 1000. / score);
+        cpus[0].memory_channels = Value::Missing;
+        let db = database(cpus, benchmarks);
+        let unscoreable = unscoreable(&db, profile, &params).unwrap();
+        assert_eq!(unscoreable.len(), 1);
+        assert_eq!(unscoreable[0].0.key.cpu.as_str(), "a");
+        assert_eq!(validate(&db, profile, &params).unwrap()[0].residuals.len(), 6);
+    }
+}
@@ -17,6 +17,32 @@ impl<T, I: Iterator<Item = Result<T>>> CollectSorted<T> for I {
 }
 
 
+pub trait CollectSortedPartitioned<T, E> {
+    /// Like `try_collect_sorted_by`, but instead of stopping at the
+    /// first error, collect the successes (sorted) and all errors.
+    fn collect_sorted_partitioned_by(
+        &mut self, cmp: impl Fn(&T, &T) -> Ordering
+    ) -> (Vec<T>, Vec<E>);
+}
+
+impl<T, E, I: Iterator<Item = Result<T, E>>> CollectSortedPartitioned<T, E> for I {
+    fn collect_sorted_partitioned_by(
+        &mut self, cmp: impl Fn(&T, &T) -> Ordering
+    ) -> (Vec<T>, Vec<E>) {
+        let mut oks = Vec::new();
+        let mut errs = Vec::new();
+        for v in self {
+            match v {
+                Ok(v) => oks.push(v),
+                Err(e) => errs.push(e),
+            }
+        }
+        oks.sort_by(cmp);
+        (oks, errs)
+    }
+}
+
+
 pub fn on<T, K>(
     key: impl Fn(&T) -> &K,
     cmp: impl Fn(&K, &K) -> Ordering
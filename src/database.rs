@@ -0,0 +1,218 @@
+//! All tables and the relations between them.
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{table::{Table, Relation, RelationDef},
+            types::{CPU, SoldAt, Benchmark, ArticleName, Motherboard, MemoryKit, Shop},
+            fuzzy::did_you_mean,
+            report::{Report, Severity, Violation},
+            lint::{Suppression, lint_cpus}};
+
+
+/// The rows of all tables, for `Database::new`.
+pub struct Data {
+    pub shops: Vec<Shop>,
+    pub cpus: Vec<CPU>,
+    /// Offers for CPUs
+    pub sold_at: Vec<SoldAt>,
+    pub benchmarks: Vec<Benchmark>,
+    pub motherboards: Vec<Motherboard>,
+    pub motherboards_sold_at: Vec<SoldAt>,
+    pub memory_kits: Vec<MemoryKit>,
+    pub memory_kits_sold_at: Vec<SoldAt>,
+    pub lint_suppressions: Vec<Suppression>,
+}
+
+pub struct Database {
+    pub shops: Table<Shop>,
+    pub cpus: Table<CPU>,
+    pub sold_at: Table<SoldAt>,
+    pub sold_at_cpu: Relation<SoldAt, CPU>,
+    pub sold_at_shop: Relation<SoldAt, Shop>,
+    pub benchmarks: Table<Benchmark>,
+    pub benchmark_cpu: Relation<Benchmark, CPU>,
+    pub motherboards: Table<Motherboard>,
+    pub motherboards_sold_at: Table<SoldAt>,
+    pub motherboards_sold_at_motherboard: Relation<SoldAt, Motherboard>,
+    pub motherboards_sold_at_shop: Relation<SoldAt, Shop>,
+    pub memory_kits: Table<MemoryKit>,
+    pub memory_kits_sold_at: Table<SoldAt>,
+    pub memory_kits_sold_at_memory_kit: Relation<SoldAt, MemoryKit>,
+    pub memory_kits_sold_at_shop: Relation<SoldAt, Shop>,
+    pub lint_suppressions: Vec<Suppression>,
+}
+
+impl Database {
+    pub fn new(data: Data) -> Result<Self> {
+        let Data {
+            shops, cpus, sold_at, benchmarks, motherboards, motherboards_sold_at, memory_kits,
+            memory_kits_sold_at, lint_suppressions
+        } = data;
+        let shops = Table::new("Shop", shops)?;
+        let cpus = Table::new("CPU", cpus)?;
+        let sold_at = Table::new("SoldAt", sold_at)?;
+        let sold_at_cpu = Relation::new(RelationDef {
+            name: "SoldAt.article_name -> CPU.name",
+            key: |s: &SoldAt| &s.article_name,
+            // every offer must reference a CPU
+            broken_reference: Severity::Error,
+            // every CPU should be sold somewhere
+            min_referenced_by: Some((1, Severity::Warning)),
+        }, &sold_at);
+        let sold_at_shop = shop_relation("SoldAt.shop -> Shop.name", &sold_at);
+        let benchmarks = Table::new("Benchmark", benchmarks)?;
+        let benchmark_cpu = Relation::new(RelationDef {
+            name: "Benchmark.key.cpu -> CPU.name",
+            key: |b: &Benchmark| &b.key.cpu,
+            broken_reference: Severity::Error,
+            // most CPUs won't have results
+            min_referenced_by: None,
+        }, &benchmarks);
+        let motherboards = Table::new("Motherboard", motherboards)?;
+        let motherboards_sold_at = Table::new("MotherboardSoldAt", motherboards_sold_at)?;
+        let motherboards_sold_at_motherboard = Relation::new(RelationDef {
+            name: "MotherboardSoldAt.article_name -> Motherboard.name",
+            key: |s: &SoldAt| &s.article_name,
+            broken_reference: Severity::Error,
+            min_referenced_by: Some((1, Severity::Warning)),
+        }, &motherboards_sold_at);
+        let motherboards_sold_at_shop =
+            shop_relation("MotherboardSoldAt.shop -> Shop.name", &motherboards_sold_at);
+        let memory_kits = Table::new("MemoryKit", memory_kits)?;
+        let memory_kits_sold_at = Table::new("MemoryKitSoldAt", memory_kits_sold_at)?;
+        let memory_kits_sold_at_memory_kit = Relation::new(RelationDef {
+            name: "MemoryKitSoldAt.article_name -> MemoryKit.name",
+            key: |s: &SoldAt| &s.article_name,
+            broken_reference: Severity::Error,
+            min_referenced_by: Some((1, Severity::Warning)),
+        }, &memory_kits_sold_at);
+        let memory_kits_sold_at_shop =
+            shop_relation("MemoryKitSoldAt.shop -> Shop.name", &memory_kits_sold_at);
+        Ok(Self {
+            shops, cpus, sold_at, sold_at_cpu, sold_at_shop, benchmarks, benchmark_cpu,
+            motherboards, motherboards_sold_at, motherboards_sold_at_motherboard,
+            motherboards_sold_at_shop,
+            memory_kits, memory_kits_sold_at, memory_kits_sold_at_memory_kit,
+            memory_kits_sold_at_shop,
+            lint_suppressions,
+        })
+    }
+
+    /// Check all relations and run the lints, collecting all
+    /// violations.
+    pub fn validate(&self) -> Report {
+        let mut report = Report::default();
+        self.sold_at_cpu.check(&self.sold_at, &self.cpus, &mut report);
+        self.benchmark_cpu.check(&self.benchmarks, &self.cpus, &mut report);
+        self.motherboards_sold_at_motherboard.check(
+            &self.motherboards_sold_at, &self.motherboards, &mut report);
+        self.memory_kits_sold_at_memory_kit.check(
+            &self.memory_kits_sold_at, &self.memory_kits, &mut report);
+        for (offers, relation) in [(&self.sold_at, &self.sold_at_shop),
+                                   (&self.motherboards_sold_at, &self.motherboards_sold_at_shop),
+                                   (&self.memory_kits_sold_at, &self.memory_kits_sold_at_shop)] {
+            relation.check(offers, &self.shops, &mut report);
+            check_offers_against_shop(offers, relation, &self.shops, &mut report);
+        }
+        self.lint(&mut report);
+        report
+    }
+
+    /// Run the lints, adding violations to `report`, returning the
+    /// suppressed ones with the reasons.
+    pub fn lint(&self, report: &mut Report) -> Vec<(Violation, &'static str)> {
+        lint_cpus(self.cpus.rows().iter(), &self.lint_suppressions, report)
+    }
+
+    /// Look up a CPU by name as given by the user.
+    pub fn cpu_by_name(&self, name: &str) -> Result<&CPU> {
+        let key: ArticleName = name.into();
+        self.cpus.get(&key).ok_or_else(
+            || anyhow!("unknown CPU {name:?}{}",
+                       did_you_mean(name, self.cpus.rows().iter().map(|c| c.name.as_str()))))
+    }
+
+    /// The CPU sold in `offer`.
+    pub fn cpu_of(&self, offer: &SoldAt) -> Result<&CPU> {
+        self.sold_at_cpu.target(offer, &self.cpus).ok_or_else(
+            || anyhow!("value {:?} for foreign key {} does not exist",
+                       offer.article_name, self.sold_at_cpu.name()))
+    }
+
+    /// The motherboard sold in `offer`.
+    pub fn motherboard_of(&self, offer: &SoldAt) -> Result<&Motherboard> {
+        self.motherboards_sold_at_motherboard.target(offer, &self.motherboards).ok_or_else(
+            || anyhow!("value {:?} for foreign key {} does not exist",
+                       offer.article_name, self.motherboards_sold_at_motherboard.name()))
+    }
+
+    /// The memory kit sold in `offer`.
+    pub fn memory_kit_of(&self, offer: &SoldAt) -> Result<&MemoryKit> {
+        self.memory_kits_sold_at_memory_kit.target(offer, &self.memory_kits).ok_or_else(
+            || anyhow!("value {:?} for foreign key {} does not exist",
+                       offer.article_name, self.memory_kits_sold_at_memory_kit.name()))
+    }
+
+    /// The shop of `offer` (of any of the offer tables).
+    pub fn shop_of(&self, offer: &SoldAt) -> Result<&Shop> {
+        self.shops.get(&offer.shop).ok_or_else(
+            || anyhow!("value {:?} for foreign key {} does not exist",
+                       offer.shop, self.sold_at_shop.name()))
+    }
+
+    /// The CPU a benchmark result is for.
+    pub fn cpu_of_benchmark(&self, benchmark: &Benchmark) -> Result<&CPU> {
+        self.benchmark_cpu.target(benchmark, &self.cpus).ok_or_else(
+            || anyhow!("value {:?} for foreign key {} does not exist",
+                       benchmark.key.cpu, self.benchmark_cpu.name()))
+    }
+
+    /// The offer with URL `what`, or else all offers for the CPU named
+    /// `what`, as given by the user.
+    pub fn offers_by_url_or_cpu(&self, what: &str) -> Result<Vec<&SoldAt>> {
+        if let Some(offer) = self.sold_at.get(&what.to_string()) {
+            return Ok(vec![offer])
+        }
+        let cpu = self.cpu_by_name(what)
+            .with_context(|| anyhow!("no offer with URL {what:?}"))?;
+        Ok(self.offers_of(cpu).collect())
+    }
+
+    /// All offers for `cpu`.
+    pub fn offers_of<'s>(&'s self, cpu: &CPU) -> impl Iterator<Item = &'s SoldAt> + 's {
+        self.sold_at_cpu.sources(cpu, &self.sold_at)
+    }
+}
+
+fn shop_relation(name: &'static str, offers: &Table<SoldAt>) -> Relation<SoldAt, Shop> {
+    Relation::new(RelationDef {
+        name,
+        key: |s: &SoldAt| &s.shop,
+        broken_reference: Severity::Error,
+        // a shop may be entered before any of its offers
+        min_referenced_by: None,
+    }, offers)
+}
+
+/// Offers whose URL is not on the domain of their shop (likely a
+/// copy-paste mistake in either), or whose price is not in the
+/// currency of their shop.
+fn check_offers_against_shop(
+    offers: &Table<SoldAt>, relation: &Relation<SoldAt, Shop>, shops: &Table<Shop>,
+    report: &mut Report
+) {
+    for offer in offers.rows() {
+        let Some(shop) = relation.target(offer, shops) else { continue };
+        if ! shop.url_matches(&offer.url) {
+            report.push(Severity::Error, "SoldAt.url vs Shop.url_domain", format!(
+                "{} {:?}: URL is not on the domain {:?} of shop {:?}",
+                offers.name(), offer.url, shop.url_domain, shop.name.as_str()));
+        }
+        if offer.price.currency() != shop.currency {
+            report.push(Severity::Error, "SoldAt.price vs Shop.currency", format!(
+                "{} {:?}: price is in {:?}, shop {:?} sells in {:?}",
+                offers.name(), offer.url, offer.price.currency(), shop.name.as_str(),
+                shop.currency));
+        }
+    }
+}
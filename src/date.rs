@@ -13,17 +13,39 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::zero_prefixed_literal)]
     fn t_unixtime_from_naivedate() {
         let t = |y, m, d| {
             unixtime_from_naivedate(NaiveDate::from_ymd_opt(y, m, d).unwrap())
         };
         assert_eq!(t(2024, 04, 01), 1711929600);
     }
+
+    #[test]
+    fn t_precision() {
+        let q = Date::try_from("Q1'20").unwrap();
+        assert_eq!(q.precision(), DatePrecision::Quarter);
+        assert_eq!(q.uncertainty_days(), 45);
+        assert_eq!(q.add_days(-45).unixtime(), Date::new(2020, 1, 1).unwrap().unixtime());
+        let (first, last) = Date::try_from("Q4'21").unwrap().bounds();
+        assert_eq!(first.unixtime(), Date::new(2021, 10, 1).unwrap().unixtime());
+        assert_eq!(last.unixtime(), Date::new(2021, 12, 31).unwrap().unixtime());
+        assert_eq!(last.precision(), DatePrecision::Quarter);
+        assert_eq!(Date::try_from("6/13/2023").unwrap().precision(), DatePrecision::Day);
+    }
 }
 
 
+/// How exactly a `Date` is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatePrecision {
+    Day,
+    /// Only the quarter is known, the date is the middle of it
+    Quarter,
+}
+
 #[derive(Debug, Clone)]
-pub struct Date(NaiveDate);
+pub struct Date(NaiveDate, DatePrecision);
 impl Date {
     pub fn new(
         year: u16,
@@ -35,7 +57,7 @@ impl Date {
         if month > 12 { bail!("month too large") }
         if mday > 31 { bail!("mday too large") }
         if let Some(nd) = NaiveDate::from_ymd_opt(year as i32, month as u32, mday as u32) {
-            Ok(Self(nd))
+            Ok(Self(nd, DatePrecision::Day))
         } else {
             bail!("invalid mday {mday} for {year}/{month}")
         }
@@ -44,7 +66,7 @@ impl Date {
 
 impl From<NaiveDate> for Date {
     fn from(nd: NaiveDate) -> Self {
-        Self(nd)
+        Self(nd, DatePrecision::Day)
     }
 }
 
@@ -60,6 +82,53 @@ impl Date {
     pub fn unixtime(&self) -> i64 {
         unixtime_from_naivedate(self.0)
     }
+
+    pub fn year(&self) -> u16 {
+        self.0.year() as u16 // range checked in `new`
+    }
+
+    pub fn month(&self) -> u8 {
+        self.0.month() as u8
+    }
+
+    pub fn mday(&self) -> u8 {
+        self.0.day() as u8
+    }
+
+    pub fn precision(&self) -> DatePrecision {
+        self.1
+    }
+
+    /// How many days the actual date may be before or after this one.
+    pub fn uncertainty_days(&self) -> i64 {
+        match self.1 {
+            DatePrecision::Day => 0,
+            DatePrecision::Quarter => 45,
+        }
+    }
+
+    /// The first and last day the actual date may be on.
+    pub fn bounds(&self) -> (Date, Date) {
+        match self.1 {
+            DatePrecision::Day => (self.clone(), self.clone()),
+            DatePrecision::Quarter => {
+                let (y, m) = (self.0.year(), self.0.month0() / 3 * 3 + 1);
+                let first = NaiveDate::from_ymd_opt(y, m, 1).expect("valid month");
+                let next = if m == 10 {
+                    NaiveDate::from_ymd_opt(y + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(y, m + 3, 1)
+                }.expect("valid month");
+                (Self(first, self.1), Self(next.pred_opt().expect("not the first day"), self.1))
+            }
+        }
+    }
+
+    /// The date `days` later (or earlier if negative), with the same
+    /// precision.
+    pub fn add_days(&self, days: i64) -> Date {
+        Self(self.0 + chrono::Duration::days(days), self.1)
+    }
 }
 
 impl Serialize for Date {
@@ -99,6 +168,7 @@ impl TryFrom<&str> for Date {
     type Error = anyhow::Error;
 
     /// "6/13/2023", "Q1'20", "August 7, 2019"
+    #[allow(clippy::single_match)]
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         (|| -> Result<Self, Self::Error> {
             // "August 7, 2019"
@@ -133,13 +203,14 @@ impl TryFrom<&str> for Date {
                                 4 => yearstr.parse()?,
                                 _ => bail!("2 or 4 characters representing year expected after '\''")
                             };
-                        Ok(match *quarterstr {
+                        let date = match *quarterstr {
                             "Q1" => Date::new(year, 2, 15)?,
                             "Q2" => Date::new(year, 5, 15)?,
                             "Q3" => Date::new(year, 8, 15)?,
                             "Q4" => Date::new(year, 11, 15)?,
                             _ => bail!("expecting Q{{1,2,3,4}} before '\''")
-                        }.into())
+                        };
+                        Ok(Date(date.0, DatePrecision::Quarter))
                     } else {
                         panic!()
                     }
@@ -0,0 +1,108 @@
+//! Approximate string matching, to suggest what a broken foreign key
+//! was probably meant to be.
+
+use std::collections::HashSet;
+
+
+/// Lowercase, without trademark signs, and with whitespace collapsed.
+pub fn normalise(s: &str) -> String {
+    let s: String = s.chars()
+        .filter(|c| ! matches!(c, '™' | '®' | '©'))
+        .flat_map(|c| c.to_lowercase())
+        .collect();
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Levenshtein distance, in chars.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut diag = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let old = row[j];
+            row[j] = if a[i - 1] == b[j - 1] {
+                diag
+            } else {
+                1 + diag.min(row[j]).min(row[j - 1])
+            };
+            diag = old;
+        }
+    }
+    row[b.len()]
+}
+
+fn tokens(s: &str) -> HashSet<&str> {
+    s.split(|c: char| c.is_whitespace() || c == '-').filter(|t| ! t.is_empty()).collect()
+}
+
+/// The mean of the normalised edit distance and the token overlap
+/// (Jaccard index), from 0. (nothing in common) to 1. (equal after
+/// normalisation).
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let a = normalise(a);
+    let b = normalise(b);
+    let maxlen = a.chars().count().max(b.chars().count());
+    if maxlen == 0 {
+        return 1.
+    }
+    let edit = 1. - edit_distance(&a, &b) as f32 / maxlen as f32;
+    let ta = tokens(&a);
+    let tb = tokens(&b);
+    let union = ta.union(&tb).count();
+    let overlap =
+        if union == 0 { 0. } else { ta.intersection(&tb).count() as f32 / union as f32 };
+    (edit + overlap) / 2.
+}
+
+/// Up to `n` of the `candidates` most similar to `needle`, best first,
+/// leaving out those that are too dissimilar to be useful.
+pub fn suggestions<'c>(
+    needle: &str, candidates: impl Iterator<Item = &'c str>, n: usize
+) -> Vec<&'c str> {
+    let mut scored: Vec<(f32, &str)> = candidates
+        .map(|c| (similarity(needle, c), c))
+        .filter(|(sim, _)| *sim >= 0.5)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(b.1)));
+    scored.into_iter().take(n).map(|(_, c)| c).collect()
+}
+
+/// "; did you mean \"a\" or \"b\"?", or the empty string if there are
+/// no suggestions; for appending to error messages.
+pub fn did_you_mean<'c>(needle: &str, candidates: impl Iterator<Item = &'c str>) -> String {
+    let sugg = suggestions(needle, candidates, 3);
+    if sugg.is_empty() {
+        String::new()
+    } else {
+        let sugg: Vec<String> = sugg.iter().map(|s| format!("{s:?}")).collect();
+        format!("; did you mean {}?", sugg.join(" or "))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", "abc"), 0);
+    }
+
+    #[test]
+    fn t_suggestions() {
+        let names = ["AMD Ryzen 9 7950X", "AMD Ryzen 9 7950X3D",
+                     "AMD Ryzen Threadripper 7960X", "Intel 6248R"];
+        assert_eq!(similarity("AMD Ryzen™ 9 7950X3D", "amd  ryzen 9 7950x3d"), 1.);
+        assert_eq!(suggestions("AMD Ryzen 9 7950X3d", names.into_iter(), 2),
+                   ["AMD Ryzen 9 7950X3D", "AMD Ryzen 9 7950X"]);
+        assert_eq!(suggestions("AMD Ryzen TR 7960X", names.into_iter(), 1),
+                   ["AMD Ryzen Threadripper 7960X"]);
+        assert!(suggestions("Raspberry Pi", names.into_iter(), 3).is_empty());
+    }
+}
@@ -2,8 +2,11 @@ use std::{fmt::Debug, hash::Hash, collections::HashMap};
 
 use anyhow::{bail, Result};
 
-pub trait PrimaryKey<PK> {
-    fn primary_key(&self) -> &PK;
+use crate::fuzzy::did_you_mean;
+
+pub trait PrimaryKey {
+    type Key: Debug + Clone + Eq + Hash;
+    fn primary_key(&self) -> &Self::Key;
 }
 
 
@@ -24,7 +27,7 @@ pub fn pindex_by<K: Debug + Eq + Hash, T: Debug>(
 /// Index by a field that is a foreign key on another index, checking
 /// that no foreign keys are broken.
 #[allow(unused)]
-pub fn pindex_by_foreign<'t, K: Debug + Eq + Hash, T: Debug, T2>(
+pub fn pindex_by_foreign<'t, K: Debug + Eq + Hash + AsRef<str>, T: Debug, T2>(
     items: &'t [T], key: impl Fn(&T) -> &K,
     foreign: &HashMap<&K, T2>, keyname: &str
 ) -> Result<HashMap<&'t K, &'t T>> {
@@ -32,7 +35,8 @@ pub fn pindex_by_foreign<'t, K: Debug + Eq + Hash, T: Debug, T2>(
     for item in items {
         let k = key(item);
         if ! foreign.contains_key(&k) {
-            bail!("value {k:?} for foreign key {keyname} does not exist");
+            bail!("value {k:?} for foreign key {keyname} does not exist{}",
+                  did_you_mean(k.as_ref(), foreign.keys().map(|k| k.as_ref())));
         }
         if let Some(old) = m.insert(k, item) {
             bail!("duplicate unique key {:?} used in item {item:#?} and previously {old:#?}",
@@ -43,21 +47,21 @@ pub fn pindex_by_foreign<'t, K: Debug + Eq + Hash, T: Debug, T2>(
 }
 
 /// Index by a field that is a foreign key on another index, checking
-/// that no foreign keys are broken.
-#[allow(unused)]
+/// that no foreign keys are broken; each key maps to the items with
+/// it, by their primary key.
 pub fn mindex_by_foreign<'t,
-                         K: Debug + Eq + Hash,
-                         PK: Debug + Eq + Hash,
-                         T: Debug + PrimaryKey<PK>,
+                         K: Debug + Eq + Hash + AsRef<str>,
+                         T: Debug + PrimaryKey,
                          T2>(
     items: &'t [T], key: impl Fn(&T) -> &K,
     foreign: &HashMap<&K, T2>, keyname: &str
-) -> Result<HashMap<&'t K, HashMap<&'t PK, &'t T>>> {
-    let mut m: HashMap<&'t K, HashMap<&'t PK, &'t T>> = HashMap::new();
+) -> Result<HashMap<&'t K, HashMap<&'t T::Key, &'t T>>> {
+    let mut m: HashMap<&'t K, HashMap<&'t T::Key, &'t T>> = HashMap::new();
     for item in items {
         let k = key(item);
         if ! foreign.contains_key(&k) {
-            bail!("value {k:?} for foreign key {keyname} does not exist");
+            bail!("value {k:?} for foreign key {keyname} does not exist{}",
+                  did_you_mean(k.as_ref(), foreign.keys().map(|k| k.as_ref())));
         }
         let pk = item.primary_key();
         if let Some(m2) = m.get_mut(k) {
@@ -0,0 +1,175 @@
+//! The landed price of an offer: what it costs delivered to the
+//! buyer's country, with shipping, VAT and import charges, in CHF.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{types::{Country, Currency, Price, Shipping, Shop}, value::Value};
+
+
+/// Where the buyer is, and the assumptions about crossing borders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LandedParams {
+    pub destination: Country,
+    pub chf_per_eur: f32,
+    /// Of the goods value, when crossing a customs border (Switzerland
+    /// to or from the EU)
+    pub import_duty_rate: f32,
+    /// Charged by the carrier for the customs clearance, in CHF
+    pub clearance_fee_chf: f32,
+}
+
+impl Default for LandedParams {
+    fn default() -> Self {
+        LandedParams {
+            destination: Country::CH,
+            // a guess; change it to the current rate
+            chf_per_eur: 0.95,
+            // Switzerland abolished its industrial tariffs in 2024;
+            // the EU has none on CPUs (ITA)
+            import_duty_rate: 0.,
+            // a guess; carriers charge about 10 to 30 CHF
+            clearance_fee_chf: 20.,
+        }
+    }
+}
+
+/// Standard VAT rate of `country`.
+pub fn vat_rate(country: Country) -> f32 {
+    match country {
+        Country::CH => 0.081,
+        Country::DE => 0.19,
+        Country::AT => 0.20,
+        Country::FR => 0.20,
+        Country::IT => 0.22,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LandedPrice {
+    /// As listed (for all items), converted to CHF
+    pub list_chf: f32,
+    /// VAT of the shop's country included in `list_chf` that is not
+    /// charged on exports (subtracted, thus <= 0)
+    pub foreign_vat_chf: f32,
+    /// None if the shop's shipping costs are unknown (then counted as
+    /// 0)
+    pub shipping_chf: Option<f32>,
+    /// VAT of the destination country not included in the list price
+    pub vat_chf: f32,
+    pub duty_chf: f32,
+    pub clearance_fee_chf: f32,
+    pub total_chf: f32,
+}
+
+pub fn in_chf(params: &LandedParams, currency: Currency, amount: f32) -> f32 {
+    match currency {
+        Currency::CHF => amount,
+        Currency::EUR => amount * params.chf_per_eur,
+    }
+}
+
+/// Landed price of `quantity` items at `price` each from `shop`, as a
+/// single order (shipped once).
+pub fn landed_price(
+    params: &LandedParams, shop: &Shop, price: Price, quantity: u8
+) -> LandedPrice {
+    landed_order(params, shop, &[(price, quantity)])
+}
+
+/// Landed price of a single order (shipped once, cleared once) from
+/// `shop` of `items`, each a price (in the shop's currency) and a
+/// quantity.
+pub fn landed_order(params: &LandedParams, shop: &Shop, items: &[(Price, u8)]) -> LandedPrice {
+    let chf = |amount: f32| in_chf(params, shop.currency, amount);
+    let list: f32 = items.iter()
+        .map(|(price, quantity)| price.amount() as f32 * *quantity as f32)
+        .sum();
+    let list_chf = chf(list);
+    let shipping_chf = match &shop.shipping {
+        Value::Some(s) | Value::SomeWithDoubts(s, _) => Some(match s {
+            Shipping::Free => 0.,
+            Shipping::Flat(cost) => chf(*cost as f32),
+            Shipping::FreeFrom { cost, free_from } =>
+                if list >= *free_from as f32 { 0. } else { chf(*cost as f32) },
+        }),
+        Value::NotApplicable => Some(0.),
+        Value::Missing => None,
+    };
+    let shipping = shipping_chf.unwrap_or(0.);
+    let domestic = shop.country == params.destination;
+    let origin_vat = vat_rate(shop.country);
+    let net_chf = if shop.prices_include_vat { list_chf / (1. + origin_vat) } else { list_chf };
+    let (foreign_vat_chf, vat_chf, duty_chf, clearance_fee_chf) = if domestic {
+        let vat = if shop.prices_include_vat { 0. } else { net_chf * origin_vat };
+        (0., vat, 0., 0.)
+    } else {
+        let vat = (net_chf + shipping) * vat_rate(params.destination);
+        let customs_border = shop.country.is_eu() != params.destination.is_eu();
+        let (duty, fee) = if customs_border {
+            (net_chf * params.import_duty_rate, params.clearance_fee_chf)
+        } else {
+            (0., 0.)
+        };
+        (net_chf - list_chf, vat, duty, fee)
+    };
+    LandedPrice {
+        list_chf,
+        foreign_vat_chf,
+        shipping_chf,
+        vat_chf,
+        duty_chf,
+        clearance_fee_chf,
+        total_chf: list_chf + foreign_vat_chf + shipping + vat_chf + duty_chf + clearance_fee_chf,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shop(country: Country, currency: Currency) -> Shop {
+        Shop {
+            name: "Test".into(),
+            url_domain: "example.com".into(),
+            country,
+            currency,
+            shipping: Shipping::FreeFrom { cost: 10, free_from: 100 }.into(),
+            prices_include_vat: true,
+        }
+    }
+
+    #[test]
+    fn t_landed_price() {
+        let params = LandedParams::default();
+        let ch = shop(Country::CH, Currency::CHF);
+        let p = landed_price(&params, &ch, Price::CHF(500), 1);
+        assert_eq!(p.total_chf, 500.);
+        let p = landed_price(&params, &ch, Price::CHF(50), 1);
+        assert_eq!(p.total_chf, 60.);
+        // shipped together, the order is worth enough for free shipping
+        let p = landed_order(&params, &ch, &[(Price::CHF(50), 1), (Price::CHF(30), 2)]);
+        assert_eq!(p.total_chf, 110.);
+
+        let net_prices = Shop { prices_include_vat: false, ..ch.clone() };
+        let p = landed_price(&params, &net_prices, Price::CHF(1000), 1);
+        assert_eq!(p.foreign_vat_chf, 0.);
+        assert!((p.vat_chf - 81.).abs() < 0.01);
+        assert_eq!(p.clearance_fee_chf, 0.);
+        assert!((p.total_chf - 1081.).abs() < 0.01);
+
+        let de = shop(Country::DE, Currency::EUR);
+        let p = landed_price(&params, &de, Price::EUR(119), 2);
+        let net = 2. * 119. * params.chf_per_eur / 1.19;
+        assert!((p.list_chf + p.foreign_vat_chf - net).abs() < 0.01);
+        assert_eq!(p.shipping_chf, Some(0.));
+        assert!((p.vat_chf - net * 0.081).abs() < 0.01);
+        assert_eq!(p.clearance_fee_chf, params.clearance_fee_chf);
+
+        let within_eu = LandedParams { destination: Country::AT, ..params };
+        let p = landed_price(&within_eu, &de, Price::EUR(119), 1);
+        assert_eq!(p.clearance_fee_chf, 0.);
+        assert!((p.vat_chf - 100. * within_eu.chf_per_eur * 0.2).abs() < 0.01);
+    }
+}
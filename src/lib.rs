@@ -6,3 +6,17 @@ pub mod name;
 pub mod index;
 pub mod set;
 pub mod collectsorted;
+pub mod scoring;
+pub mod table;
+pub mod database;
+pub mod report;
+pub mod fuzzy;
+pub mod lint;
+pub mod tco;
+pub mod stats;
+pub mod calibration;
+pub mod uncertainty;
+pub mod pareto;
+pub mod build;
+pub mod policy;
+pub mod landed;
@@ -0,0 +1,341 @@
+//! Plausibility checks on `CPU` records. Each rule has a name (used
+//! for suppressing it for particular records) and a severity.
+
+use crate::{types::{CPU, Usage, Brand},
+            report::{Report, Severity, Violation}};
+
+
+pub struct Rule {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+    /// Adds a message for every problem found in the record.
+    pub check: fn(&CPU, &mut Vec<String>),
+}
+
+/// Exempts a record from a rule, e.g. when the manufacturer's data is
+/// known to be odd.
+pub struct Suppression {
+    pub rule: &'static str,
+    /// Primary key of the record
+    pub record: &'static str,
+    pub reason: &'static str,
+}
+
+
+fn threads_vs_cores(cpu: &CPU, msgs: &mut Vec<String>) {
+    if let (Some(cores), Some(threads)) = (cpu.cores.get(), cpu.threads.get()) {
+        if threads < cores {
+            msgs.push(format!("{threads} threads is less than {cores} cores"));
+        }
+    }
+}
+
+fn clusters_vs_totals(cpu: &CPU, msgs: &mut Vec<String>) {
+    let Some(clusters) = cpu.core_clusters.get() else { return };
+    let cores: u32 = clusters.iter().map(|c| c.count as u32).sum();
+    let threads: u32 = clusters.iter().map(|c| c.count as u32 * c.threads_per_core as u32).sum();
+    if let Some(n) = cpu.cores.get() {
+        if *n as u32 != cores {
+            msgs.push(format!("core clusters have {cores} cores, but cores is {n}"));
+        }
+    }
+    if let Some(n) = cpu.threads.get() {
+        if *n as u32 != threads {
+            msgs.push(format!("core clusters have {threads} threads, but threads is {n}"));
+        }
+    }
+}
+
+fn base_above_boost(cpu: &CPU, msgs: &mut Vec<String>) {
+    if let (Some(base), Some(boost)) = (cpu.base_clock.get(), cpu.max_boost_clock.get()) {
+        if base.0 > boost.0 {
+            msgs.push(format!("base clock {} GHz is above max boost clock {} GHz",
+                              base.0, boost.0));
+        }
+    }
+    if let Some(clusters) = cpu.core_clusters.get() {
+        for (i, c) in clusters.iter().enumerate() {
+            if let (Some(base), Some(boost)) = (c.base_clock.get(), c.max_boost_clock.get()) {
+                if base.0 > boost.0 {
+                    msgs.push(format!("core cluster {i}: base clock {} GHz is above \
+                                       max boost clock {} GHz", base.0, boost.0));
+                }
+            }
+        }
+    }
+}
+
+/// The brand, if the product line is known.
+fn brand(cpu: &CPU) -> Option<Brand> {
+    Some(cpu.product_line.get()?.0)
+}
+
+fn socket_vs_brand(cpu: &CPU, msgs: &mut Vec<String>) {
+    let (Some(brand), Some(socket)) = (brand(cpu), cpu.cpu_socket.get()) else { return };
+    if ! brand.info().socket.contains(socket) {
+        msgs.push(format!("socket {socket:?} is not one of those of brand {brand:?}"));
+    }
+}
+
+fn architecture_vs_brand(cpu: &CPU, msgs: &mut Vec<String>) {
+    let (Some(brand), Some(arch)) = (brand(cpu), cpu.architecture.get()) else { return };
+    if ! brand.info().microarchitectures.contains(arch) {
+        msgs.push(format!("architecture {arch:?} is not one of those of brand {brand:?}"));
+    }
+}
+
+fn usage_vs_brand(cpu: &CPU, msgs: &mut Vec<String>) {
+    let Some(brand) = brand(cpu) else { return };
+    let Some(usage) = cpu.product_line.get().and_then(|pl| pl.1.get()) else { return };
+    let brand_usage = brand.info().usage;
+    let compatible = |a: &Usage, b: &Usage| {
+        a == b || matches!((a, b),
+                           (Usage::ServerOrEmbedded, Usage::Server | Usage::Embedded)
+                           | (Usage::Server | Usage::Embedded, Usage::ServerOrEmbedded))
+    };
+    if ! compatible(usage, &brand_usage) {
+        msgs.push(format!("product line usage {usage:?} does not match usage \
+                           {brand_usage:?} of brand {brand:?}"));
+    }
+}
+
+fn launch_before_brand(cpu: &CPU, msgs: &mut Vec<String>) {
+    let (Some(brand), Some(date)) = (brand(cpu), cpu.launch_date.get()) else { return };
+    let first = brand.info().first_release_year;
+    if date.year() < first {
+        msgs.push(format!("launched in {} before brand {brand:?} was first released in {first}",
+                          date.year()));
+    }
+}
+
+/// EPYC models with a "P" suffix (e.g. 7502P) are the cheaper
+/// single-socket variants.
+fn p_suffix_single_socket(cpu: &CPU, msgs: &mut Vec<String>) {
+    let name = cpu.name.as_str();
+    let Some(model) = name.split_whitespace().last() else { return };
+    let p_model = name.contains("EPYC") && model.ends_with('P')
+        && model.starts_with(|c: char| c.is_ascii_digit());
+    if let (true, Some(n)) = (p_model, cpu.max_sockets.get()) {
+        if *n > 1 {
+            msgs.push(format!("{model} is a single-socket model, but max_sockets is {n}"));
+        }
+    }
+}
+
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        name: "threads-vs-cores",
+        severity: Severity::Error,
+        description: "a CPU has at least as many threads as cores",
+        check: threads_vs_cores,
+    },
+    Rule {
+        name: "clusters-vs-totals",
+        severity: Severity::Error,
+        description: "core clusters add up to the cores and threads of the CPU",
+        check: clusters_vs_totals,
+    },
+    Rule {
+        name: "base-above-boost",
+        severity: Severity::Error,
+        description: "base clock is not above max boost clock",
+        check: base_above_boost,
+    },
+    Rule {
+        name: "socket-vs-brand",
+        severity: Severity::Error,
+        description: "socket is one of the sockets of the brand",
+        check: socket_vs_brand,
+    },
+    Rule {
+        name: "architecture-vs-brand",
+        severity: Severity::Warning,
+        description: "architecture is one of the microarchitectures of the brand",
+        check: architecture_vs_brand,
+    },
+    Rule {
+        name: "usage-vs-brand",
+        severity: Severity::Warning,
+        description: "usage given in the product line matches the usage of the brand",
+        check: usage_vs_brand,
+    },
+    Rule {
+        name: "launch-before-brand",
+        severity: Severity::Warning,
+        description: "launch date is not before the first release of the brand",
+        check: launch_before_brand,
+    },
+    Rule {
+        name: "p-suffix-single-socket",
+        severity: Severity::Error,
+        description: "EPYC \"P\" models support a single socket only",
+        check: p_suffix_single_socket,
+    },
+];
+
+
+/// Run all `RULES` on all `cpus`, adding violations to `report`
+/// unless suppressed; returns the suppressed violations with the
+/// reasons for suppressing them.
+pub fn lint_cpus<'c>(
+    cpus: impl Iterator<Item = &'c CPU>,
+    suppressions: &[Suppression],
+    report: &mut Report,
+) -> Vec<(Violation, &'static str)> {
+    for s in suppressions {
+        if ! RULES.iter().any(|r| r.name == s.rule) {
+            report.push(Severity::Error, "lint", format!(
+                "suppression for {:?} names unknown rule {:?}", s.record, s.rule));
+        }
+    }
+    let mut used = vec![false; suppressions.len()];
+    let mut suppressed = Vec::new();
+    for cpu in cpus {
+        for rule in RULES {
+            let mut msgs = Vec::new();
+            (rule.check)(cpu, &mut msgs);
+            for msg in msgs {
+                let violation = Violation {
+                    severity: rule.severity,
+                    source: rule.name.into(),
+                    message: format!("CPU {:?}: {msg}", cpu.name.as_str()),
+                };
+                if let Some(i) = suppressions.iter().position(
+                    |s| s.rule == rule.name && s.record == cpu.name.as_str())
+                {
+                    used[i] = true;
+                    suppressed.push((violation, suppressions[i].reason));
+                } else {
+                    report.violations.push(violation);
+                }
+            }
+        }
+    }
+    for (s, used) in suppressions.iter().zip(used) {
+        if ! used {
+            report.push(Severity::Warning, "lint", format!(
+                "suppression of rule {:?} for {:?} is not needed", s.rule, s.record));
+        }
+    }
+    suppressed
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{types::{CoreCluster, ProductLine, Architecture, CPUSocket, GHz},
+                value::Value::Missing,
+                date::Date};
+    use super::*;
+
+    /// The report, and the names of the rules `cpu` violates.
+    fn lint(cpu: &CPU, suppressions: &[Suppression]) -> (Report, Vec<String>) {
+        let mut report = Report::default();
+        lint_cpus([cpu].into_iter(), suppressions, &mut report);
+        let rules = report.violations.iter()
+            .filter(|v| v.source != "lint")
+            .map(|v| v.source.clone())
+            .collect();
+        (report, rules)
+    }
+
+    fn fires(cpu: CPU) -> Vec<String> {
+        lint(&cpu, &[]).1
+    }
+
+    #[test]
+    fn t_clean() {
+        assert_eq!(fires(CPU::test_epyc_7543p()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn t_threads_vs_cores() {
+        assert_eq!(fires(CPU { threads: 16.into(), ..CPU::test_epyc_7543p() }), ["threads-vs-cores"]);
+    }
+
+    #[test]
+    fn t_clusters_vs_totals() {
+        let cluster = CoreCluster {
+            count: 16,
+            threads_per_core: 2,
+            architecture: Architecture::Zen3.into(),
+            base_clock: GHz(2.8).into(),
+            max_boost_clock: GHz(3.7).into(),
+            l2cache: Missing,
+            l3cache: Missing,
+        };
+        assert_eq!(fires(CPU { core_clusters: vec![cluster.clone()].into(), ..CPU::test_epyc_7543p() }),
+                   ["clusters-vs-totals", "clusters-vs-totals"]);
+        assert_eq!(fires(CPU { core_clusters: vec![cluster.clone(), cluster].into(),
+                               ..CPU::test_epyc_7543p() }),
+                   Vec::<String>::new());
+    }
+
+    #[test]
+    fn t_base_above_boost() {
+        assert_eq!(fires(CPU { base_clock: GHz(3.8).into(), ..CPU::test_epyc_7543p() }),
+                   ["base-above-boost"]);
+    }
+
+    #[test]
+    fn t_socket_vs_brand() {
+        assert_eq!(fires(CPU { cpu_socket: CPUSocket::AM4.into(), ..CPU::test_epyc_7543p() }),
+                   ["socket-vs-brand"]);
+    }
+
+    #[test]
+    fn t_architecture_vs_brand() {
+        assert_eq!(fires(CPU { architecture: Architecture::Zen2.into(), ..CPU::test_epyc_7543p() }),
+                   ["architecture-vs-brand"]);
+    }
+
+    #[test]
+    fn t_usage_vs_brand() {
+        let product_line = ProductLine(Brand::EPYC7003, Usage::Desktop.into()).into();
+        assert_eq!(fires(CPU { product_line, ..CPU::test_epyc_7543p() }), ["usage-vs-brand"]);
+    }
+
+    #[test]
+    fn t_launch_before_brand() {
+        let launch_date = Date::new(2019, 8, 7).unwrap().into();
+        assert_eq!(fires(CPU { launch_date, ..CPU::test_epyc_7543p() }), ["launch-before-brand"]);
+    }
+
+    #[test]
+    fn t_p_suffix_single_socket() {
+        assert_eq!(fires(CPU { max_sockets: 2.into(), ..CPU::test_epyc_7543p() }),
+                   ["p-suffix-single-socket"]);
+        assert_eq!(fires(CPU { name: "AMD EPYC 7543".into(), max_sockets: 2.into(),
+                               ..CPU::test_epyc_7543p() }),
+                   Vec::<String>::new());
+    }
+
+    fn suppression(rule: &'static str) -> Suppression {
+        Suppression { rule, record: "AMD EPYC 7543P", reason: "test" }
+    }
+
+    #[test]
+    fn t_suppression() {
+        let cpu = CPU { threads: 16.into(), ..CPU::test_epyc_7543p() };
+        let mut report = Report::default();
+        let suppressed = lint_cpus([&cpu].into_iter(), &[suppression("threads-vs-cores")],
+                                   &mut report);
+        assert!(report.violations.is_empty());
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].0.source, "threads-vs-cores");
+        assert_eq!(suppressed[0].1, "test");
+    }
+
+    #[test]
+    fn t_unused_suppression() {
+        let (report, rules) = lint(&CPU::test_epyc_7543p(), &[suppression("threads-vs-cores")]);
+        assert!(rules.is_empty());
+        assert_eq!(report.warnings().count(), 1);
+        assert!(report.violations[0].message.contains("is not needed"));
+
+        let (report, _) = lint(&CPU::test_epyc_7543p(), &[suppression("no-such-rule")]);
+        assert!(report.errors().any(|v| v.message.contains("unknown rule")));
+    }
+}
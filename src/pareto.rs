@@ -0,0 +1,49 @@
+//! Pareto fronts: the items that no other item beats in every
+//! respect.
+
+/// Whether `a` is at least as good as `b` in every objective and
+/// better in at least one; lower is better.
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y)
+}
+
+/// For each of `points` (objective values, lower is better): None if
+/// it is on the Pareto front, otherwise the index of a point on the
+/// front that dominates it (the one that is best in the first
+/// objective).
+pub fn pareto_front(points: &[Vec<f32>]) -> Vec<Option<usize>> {
+    let on_front: Vec<bool> = points.iter()
+        .map(|p| ! points.iter().any(|q| dominates(q, p)))
+        .collect();
+    points.iter().enumerate().map(|(i, p)| {
+        if on_front[i] {
+            return None
+        }
+        // A dominating point on the front always exists, as
+        // domination is transitive
+        (0..points.len())
+            .filter(|j| on_front[*j] && dominates(&points[*j], p))
+            .min_by(|a, b| points[*a][0].total_cmp(&points[*b][0]))
+    }).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_pareto_front() {
+        // (price, -score)
+        let points = vec![
+            vec![100., -10.],
+            vec![200., -30.],
+            vec![150., -5.], // dominated by 0
+            vec![300., -25.], // dominated by 1
+            vec![200., -30.], // equal to 1, not dominated
+        ];
+        assert_eq!(pareto_front(&points), [None, None, Some(0), Some(1), None]);
+        assert!(! dominates(&[1., 2.], &[1., 2.]));
+        assert!(dominates(&[1., 2.], &[1., 3.]));
+    }
+}
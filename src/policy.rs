@@ -0,0 +1,139 @@
+//! How to treat used and tray offers, which are cheaper but riskier:
+//! a shorter (or no) warranty, an unknown history, and for tray
+//! versions no cooler in the box.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{types::{CPU, SoldAt, CoolerType}, value::Value};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedOfferPolicy")]
+pub enum OfferPolicy {
+    Exclude,
+    Include,
+    /// Include, with the score counting this fraction (0..1) less
+    Discount(f32),
+}
+
+/// An `OfferPolicy` as read from a profile file, before the discount
+/// is checked.
+#[derive(Deserialize)]
+enum UncheckedOfferPolicy {
+    Exclude,
+    Include,
+    Discount(f32),
+}
+
+impl TryFrom<UncheckedOfferPolicy> for OfferPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(value: UncheckedOfferPolicy) -> Result<Self, Self::Error> {
+        match value {
+            UncheckedOfferPolicy::Exclude => Ok(OfferPolicy::Exclude),
+            UncheckedOfferPolicy::Include => Ok(OfferPolicy::Include),
+            UncheckedOfferPolicy::Discount(d) => OfferPolicy::discount(d),
+        }
+    }
+}
+
+impl OfferPolicy {
+    pub fn discount(d: f32) -> Result<Self> {
+        if ! (0. ..1.).contains(&d) {
+            bail!("a discount must be at least 0 and below 1, got {d}")
+        }
+        Ok(OfferPolicy::Discount(d))
+    }
+
+    /// The factor applied to the score of an offer the policy applies
+    /// to; None if it is excluded.
+    pub fn factor(self) -> Option<f32> {
+        match self {
+            OfferPolicy::Exclude => None,
+            OfferPolicy::Include => Some(1.),
+            OfferPolicy::Discount(d) => Some(1. - d),
+        }
+    }
+}
+
+impl TryFrom<&str> for OfferPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "exclude" => Ok(OfferPolicy::Exclude),
+            "include" => Ok(OfferPolicy::Include),
+            _ => {
+                let d: f32 = value.parse().with_context(
+                    || format!("expecting exclude, include or a discount, got {value:?}"))?;
+                OfferPolicy::discount(d)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OfferPolicies {
+    pub used: OfferPolicy,
+    pub tray: OfferPolicy,
+}
+
+impl Default for OfferPolicies {
+    fn default() -> Self {
+        OfferPolicies { used: OfferPolicy::Include, tray: OfferPolicy::Include }
+    }
+}
+
+impl OfferPolicies {
+    /// The factor applied to the score of `offer` (the product of the
+    /// discounts of both policies for a used tray offer); None if it
+    /// is excluded.
+    pub fn risk_factor(&self, offer: &SoldAt) -> Option<f32> {
+        let mut factor = 1.;
+        if offer.is_used {
+            factor *= self.used.factor()?;
+        }
+        if offer.is_tray_version {
+            factor *= self.tray.factor()?;
+        }
+        Some(factor)
+    }
+}
+
+/// A note for tray offers, which come without the cooler of the boxed
+/// version; None for other offers.
+pub fn cooler_note(offer: &SoldAt, cpu: &CPU) -> Option<&'static str> {
+    if ! offer.is_tray_version {
+        return None
+    }
+    Some(match cpu.cooler {
+        Value::Some(CoolerType::LiquidRecommended)
+            | Value::SomeWithDoubts(CoolerType::LiquidRecommended, _) =>
+            "tray: buy a cooler separately (liquid cooler recommended)",
+        _ => "tray: buy a cooler separately",
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_offer_policy() {
+        assert_eq!(OfferPolicy::try_from("exclude").unwrap(), OfferPolicy::Exclude);
+        assert_eq!(OfferPolicy::try_from("0.1").unwrap(), OfferPolicy::Discount(0.1));
+        assert!(OfferPolicy::try_from("1").is_err());
+        assert!(OfferPolicy::try_from("used").is_err());
+        assert_eq!(OfferPolicy::Discount(0.25).factor(), Some(0.75));
+        assert_eq!(OfferPolicy::Exclude.factor(), None);
+
+        let json = |s: &str| serde_json::from_str::<OfferPolicy>(s);
+        assert_eq!(json(r#"{"Discount": 0.2}"#).unwrap(), OfferPolicy::Discount(0.2));
+        assert_eq!(json(r#""Exclude""#).unwrap(), OfferPolicy::Exclude);
+        assert!(json(r#"{"Discount": 1.5}"#).is_err());
+        assert!(json(r#"{"Discount": -0.1}"#).is_err());
+    }
+}
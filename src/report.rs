@@ -0,0 +1,72 @@
+//! Collecting problems found in the data, instead of stopping at the
+//! first one.
+
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("WARNING"),
+            Severity::Error => f.write_str("ERROR"),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub severity: Severity,
+    /// Name of the relation or rule that was violated
+    pub source: String,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({})", self.severity, self.message, self.source)
+    }
+}
+
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub violations: Vec<Violation>,
+}
+
+impl Report {
+    pub fn push(&mut self, severity: Severity, source: &str, message: String) {
+        self.violations.push(Violation { severity, source: source.into(), message })
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Violation> {
+        self.violations.iter().filter(|v| v.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Violation> {
+        self.violations.iter().filter(|v| v.severity == Severity::Warning)
+    }
+
+    pub fn print(&self) {
+        for v in &self.violations {
+            println!("{v}");
+        }
+    }
+
+    /// Fails if there are any violations of `Error` severity.
+    pub fn into_result(self) -> Result<()> {
+        let n = self.errors().count();
+        if n > 0 {
+            bail!("{n} error(s) found in the data")
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,790 @@
+//! Performance estimates for CPUs, one per purpose ("profile").
+
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{types::{CPU, CoreCluster, Architecture, Brand}, date::Date, tco::TcoParams,
+            policy::OfferPolicies, landed::LandedParams, value::Value};
+
+
+/// The purpose a CPU is being evaluated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Profile {
+    Compilation,
+    AI,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 2] = [Profile::Compilation, Profile::AI];
+}
+
+impl TryFrom<&str> for Profile {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "compile" | "compilation" => Ok(Profile::Compilation),
+            "ai" | "AI" => Ok(Profile::AI),
+            _ => bail!("unknown profile {value:?}, expecting compile or ai")
+        }
+    }
+}
+
+/// Where the per-core speed of a core cluster was taken from.
+#[derive(Debug, Clone)]
+pub enum SpeedSource {
+    /// From `ProfileParams.ipc`
+    Architecture(Architecture),
+    /// From `BrandInfo.epyc_speed`, if the architecture is unknown or
+    /// not in the table
+    Brand(Brand),
+    /// Fallback if neither of the above is available
+    LaunchDate,
+}
+
+/// The tunable constants of a profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileParams {
+    /// Per-core speed at the same clock relative to Zen 2, per
+    /// architecture (see `IPC` for the defaults)
+    pub ipc: BTreeMap<Architecture, f32>,
+    /// Weight of a thread beyond the first one on a core, relative to
+    /// a core
+    pub smt_factor: f32,
+    /// Years in which per-core speed doubles, for CPUs whose speed is
+    /// estimated from the launch date
+    pub doubling_period_years: f32,
+    /// Memory bandwidth per thread (GB/s) at and above which memory is
+    /// not a bottleneck
+    pub bandwidth_per_thread_needed: f32,
+    /// How strongly the score drops with less bandwidth: it is
+    /// multiplied with (available / needed) ^ bandwidth_elasticity
+    pub bandwidth_elasticity: f32,
+    /// L2 + L3 cache per core (MB) which gives a cache factor of 1
+    pub cache_reference_per_core: f32,
+    /// The score of a core cluster is multiplied with (cache per core
+    /// / cache_reference_per_core) ^ cache_weight; 0 for no effect
+    pub cache_weight: f32,
+    /// Share of a job (0..=1) that runs on all threads; the rest runs
+    /// on a single core at its max boost clock (Amdahl's law)
+    pub parallel_fraction: f32,
+    /// Parallel throughput of each CPU in a multi-socket machine
+    /// relative to the same CPU alone, as threads access memory
+    /// attached to the other sockets and keep caches coherent
+    pub multi_socket_efficiency: f32,
+    /// Size of a job (for compilation: a typical rebuild) in seconds
+    /// on a single Zen 2 core at 1 GHz
+    pub job_work: f32,
+}
+
+/// A profile with its parameters, as stored in a JSON file (e.g. by
+/// the `fit` command).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub profile: Profile,
+    pub params: ProfileParams,
+    /// Used when ranking by total cost of ownership
+    #[serde(default)]
+    pub tco: TcoParams,
+    /// How used and tray offers are treated
+    #[serde(default)]
+    pub offers: OfferPolicies,
+    /// Where the offers are delivered to, for their landed prices
+    #[serde(default)]
+    pub landed: LandedParams,
+    /// Extra purchase cost of a multi-socket machine per additional
+    /// socket (the dearer board, another cooler), in CHF
+    #[serde(default = "default_multi_socket_premium_chf")]
+    pub multi_socket_premium_chf: f32,
+}
+
+fn default_multi_socket_premium_chf() -> f32 {
+    // a guess: dual-socket server boards cost a few hundred CHF more
+    // than single-socket ones
+    300.
+}
+
+/// Overwrite the entries in `base` with those in `over`, recursively
+/// for objects.
+fn merge_json(base: &mut serde_json::Value, over: serde_json::Value) {
+    match (base, over) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(over)) => {
+            for (k, v) in over {
+                match base.get_mut(&k) {
+                    Some(b) => merge_json(b, v),
+                    None => { base.insert(k, v); }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+impl ProfileConfig {
+    pub fn new(profile: Profile) -> Self {
+        ProfileConfig {
+            profile,
+            params: profile.default_params(),
+            tco: TcoParams::default(),
+            offers: OfferPolicies::default(),
+            landed: LandedParams::default(),
+            multi_socket_premium_chf: default_multi_socket_premium_chf(),
+        }
+    }
+
+    /// Extra purchase cost of a machine with `sockets` CPUs over a
+    /// single-socket one, in CHF.
+    pub fn platform_chf(&self, sockets: u8) -> f32 {
+        self.multi_socket_premium_chf * sockets.saturating_sub(1) as f32
+    }
+
+    /// Parameters missing in `s` (which must at least name the
+    /// profile) are taken from the defaults of the profile, thus a
+    /// file can contain just the changed ones.
+    pub fn from_json(s: &str) -> Result<Self> {
+        let file: serde_json::Value = serde_json::from_str(s)?;
+        let Some(profile) = file.get("profile") else { bail!("missing \"profile\" entry") };
+        let profile: Profile = serde_json::from_value(profile.clone())?;
+        let mut config = serde_json::to_value(ProfileConfig::new(profile))?;
+        merge_json(&mut config, file);
+        Ok(serde_json::from_value(config)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("reading profile file {path:?}"))?;
+        Self::from_json(&s).with_context(|| format!("parsing profile file {path:?}"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let s = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, s + "\n").with_context(|| format!("writing profile file {path:?}"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClusterPerformance {
+    pub cluster: CoreCluster,
+    /// Cores plus additional threads weighted by `smt_factor`
+    pub coresthreads: f32,
+    /// Per-core speed at the same clock relative to Zen 2
+    pub speed: f32,
+    pub speed_source: SpeedSource,
+    /// L2 plus the core's share of L3, in MB, if known
+    pub cache_per_core: Option<f32>,
+    /// 1 if `cache_per_core` is unknown
+    pub cache_factor: f32,
+    /// Score using all threads at base clock
+    pub score: f32,
+    /// Score of a single core at max boost clock; None if that is
+    /// unknown and not needed (`parallel_fraction` 1)
+    pub serial_score: Option<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Performance {
+    /// Higher is better; equals `parallel_score` if
+    /// `parallel_fraction` is 1
+    pub score: f32,
+    /// Sum of the cluster scores, with `bandwidth_factor` applied,
+    /// times the number of sockets and `multi_socket_efficiency` if
+    /// more than 1
+    pub parallel_score: f32,
+    /// Best `serial_score` of all clusters; None if there is none
+    pub serial_score: Option<f32>,
+    /// Estimated time for a job (e.g. a rebuild), in seconds
+    pub job_time: f32,
+    /// Of a single CPU
+    pub clusters: Vec<ClusterPerformance>,
+    /// Number of CPUs in the machine
+    pub sockets: u8,
+    /// Theoretical, GB/s, of all sockets
+    pub memory_bandwidth: f32,
+    pub bandwidth_per_thread: f32,
+    /// The factor applied to the parallel score for memory
+    /// saturation, <= 1
+    pub bandwidth_factor: f32,
+}
+
+impl Profile {
+    pub fn default_params(self) -> ProfileParams {
+        let ipc = IPC.iter().cloned().collect();
+        match self {
+            Profile::Compilation => ProfileParams {
+                ipc,
+                smt_factor: 0.3,
+                // Twice as fast per core every 5 years? In the last 5y
+                // anyway?
+                doubling_period_years: 5.,
+                // guesses; compilers are not very memory bound
+                bandwidth_per_thread_needed: 3.,
+                bandwidth_elasticity: 0.3,
+                // Zen 2 to 4 have 4.5 to 5 MB per core; X3D models
+                // compile a few percent faster
+                cache_reference_per_core: 4.,
+                cache_weight: 0.1,
+                // incremental rebuilds often wait for a few serial
+                // crates (or the linker)
+                parallel_fraction: 0.95,
+                // a guess; compiler processes mostly use their own
+                // memory
+                multi_socket_efficiency: 0.9,
+                // 30 s if it were fully parallel on 16 Zen 2 cores with
+                // SMT (16 + 16 x 0.3 = 20.8 core-threads) at 3.4 GHz;
+                // until SMT was counted here (16 core-threads), all
+                // job times of this profile were 1.3 times shorter
+                job_work: 30. * 20.8 * 3.4,
+            },
+            Profile::AI => ProfileParams {
+                ipc,
+                smt_factor: 0.,
+                doubling_period_years: 5.,
+                // inference is mostly limited by memory bandwidth
+                bandwidth_per_thread_needed: 8.,
+                bandwidth_elasticity: 1.,
+                // model weights don't fit in cache anyway
+                cache_reference_per_core: 4.,
+                cache_weight: 0.05,
+                // matrix multiplications parallelise well
+                parallel_fraction: 1.,
+                // a guess; model weights are spread over the memory
+                // of both sockets
+                multi_socket_efficiency: 0.8,
+                // one answer: 20 s on 16 Zen 2 cores at 3.4 GHz (when
+                // not memory bound); SMT doesn't count here
+                job_work: 20. * 16. * 3.4,
+            },
+        }
+    }
+
+    /// A performance estimate score: the parallel part of a job runs
+    /// on all core clusters at base clock, with a penalty for too
+    /// little memory bandwidth for its threads, the serial part on the
+    /// fastest core at max boost clock.
+    pub fn performance(self, params: &ProfileParams, cpu: &CPU) -> Result<Performance> {
+        self.performance_with_sockets(params, cpu, 1)
+    }
+
+    /// Like `performance`, for a machine with `sockets` of `cpu`,
+    /// each with its own memory channels.
+    pub fn performance_with_sockets(
+        self, params: &ProfileParams, cpu: &CPU, sockets: u8
+    ) -> Result<Performance> {
+        let mut inputs = Inputs::default();
+        if sockets > 1 {
+            if let Some(max) = inputs.get(&cpu.max_sockets, || "max_sockets".into()) {
+                if sockets > *max {
+                    bail!("{} supports at most {max} socket(s), not {sockets}",
+                          cpu.name.as_str())
+                }
+            }
+        }
+        let clusters = match &cpu.core_clusters {
+            Value::NotApplicable => {
+                let cores = inputs.get(&cpu.cores, || "cores".into());
+                let threads = inputs.get(&cpu.threads, || "threads".into());
+                if cores.is_some() && threads.is_some() { cpu.clusters()? } else { Vec::new() }
+            }
+            clusters => inputs.get(clusters, || "core_clusters".into())
+                .cloned().unwrap_or_default(),
+        };
+        let prefix = |i| match cpu.core_clusters {
+            Value::NotApplicable => String::new(),
+            _ => format!("core_clusters[{i}]."),
+        };
+        let cores: u32 = clusters.iter().map(|c| c.count as u32).sum();
+        let mut score = 0.;
+        let mut serial_score: Option<f32> = None;
+        let mut cluster_perfs = Vec::new();
+        let mut threads = 0;
+        for (i, cluster) in clusters.iter().enumerate() {
+            let speed = per_core_speed(params, cpu, cluster, &mut inputs, &prefix(i))?;
+            let base_clock = inputs.get(&cluster.base_clock, || format!("{}base_clock", prefix(i)));
+            // the max boost clock is only needed for a serial part
+            let max_boost_clock = if params.parallel_fraction < 1. {
+                inputs.get(&cluster.max_boost_clock, || format!("{}max_boost_clock", prefix(i)))
+            } else {
+                cluster.max_boost_clock.get()
+            };
+            let (Some((speed, speed_source)), Some(_)) = (speed, base_clock) else { continue };
+            let cache_per_core = cache_per_core(cluster, cores);
+            let cache_factor = cache_per_core.map(|c| cache_factor(params, c)).unwrap_or(1.);
+            let cluster_score = cache_factor * match self {
+                Profile::Compilation =>
+                    anticipated_compilation_performance(params, cluster, speed)?,
+                Profile::AI =>
+                    anticipated_ai_performance(params, cluster, speed)?,
+            };
+            let cluster_serial_score = match max_boost_clock {
+                Some(_) => Some(cache_factor * anticipated_serial_performance(cluster, speed)?),
+                None => None,
+            };
+            score += cluster_score;
+            if let Some(s) = cluster_serial_score {
+                serial_score = Some(serial_score.map_or(s, |best: f32| best.max(s)));
+            }
+            cluster_perfs.push(ClusterPerformance {
+                cluster: cluster.clone(),
+                coresthreads: coresthreads(cluster, params.smt_factor),
+                speed, speed_source, cache_per_core, cache_factor, score: cluster_score,
+                serial_score: cluster_serial_score,
+            });
+            threads += cluster.count as u32 * cluster.threads_per_core as u32;
+        }
+        inputs.get(&cpu.memory_channels, || "memory_channels".into());
+        inputs.get(&cpu.system_memory_specification, || "system_memory_specification".into());
+        inputs.check()?;
+        let memory_bandwidth = cpu.memory_bandwidth()? * sockets as f32;
+        let bandwidth_per_thread = memory_bandwidth / (threads * sockets as u32) as f32;
+        let bandwidth_factor = bandwidth_factor(params, bandwidth_per_thread);
+        let mut parallel_score = score * bandwidth_factor;
+        if sockets > 1 {
+            parallel_score *= sockets as f32 * params.multi_socket_efficiency;
+        }
+        let score = match serial_score {
+            Some(serial_score) => amdahl(params.parallel_fraction, serial_score, parallel_score),
+            None => parallel_score,
+        };
+        Ok(Performance {
+            score,
+            parallel_score,
+            serial_score,
+            job_time: params.job_work / score,
+            clusters: cluster_perfs,
+            sockets,
+            memory_bandwidth,
+            bandwidth_per_thread,
+            bandwidth_factor,
+        })
+    }
+}
+
+/// L2 of a core plus its share of L3 (assumed shared by all `cores`
+/// of the CPU), in MB.
+fn cache_per_core(cluster: &CoreCluster, cores: u32) -> Option<f32> {
+    let mb = |b: u64| b as f32 / (1024. * 1024.);
+    let l2 = mb(cluster.l2cache.get()?.in_bytes()) / cluster.count as f32;
+    let l3 = mb(cluster.l3cache.get()?.in_bytes()) / cores as f32;
+    Some(l2 + l3)
+}
+
+pub fn cache_factor(params: &ProfileParams, cache_per_core: f32) -> f32 {
+    (cache_per_core / params.cache_reference_per_core).powf(params.cache_weight)
+}
+
+/// How much of the score difference between two CPUs is due to their
+/// different caches.
+#[derive(Debug)]
+pub struct CacheComparison {
+    pub a: Performance,
+    pub b: Performance,
+    /// Scores with `cache_weight` set to 0
+    pub a_without_cache: f32,
+    pub b_without_cache: f32,
+}
+
+impl CacheComparison {
+    pub fn difference(&self) -> f32 {
+        self.a.score - self.b.score
+    }
+
+    pub fn attributable_to_cache(&self) -> f32 {
+        self.difference() - (self.a_without_cache - self.b_without_cache)
+    }
+}
+
+pub fn compare_cache(
+    profile: Profile, params: &ProfileParams, a: &CPU, b: &CPU
+) -> Result<CacheComparison> {
+    let without = ProfileParams { cache_weight: 0., ..params.clone() };
+    Ok(CacheComparison {
+        a: profile.performance(params, a)?,
+        b: profile.performance(params, b)?,
+        a_without_cache: profile.performance(&without, a)?.score,
+        b_without_cache: profile.performance(&without, b)?.score,
+    })
+}
+
+impl Performance {
+    /// How the score was derived from the core clusters, memory
+    /// bandwidth and sockets, one line per factor.
+    pub fn explain(&self, params: &ProfileParams) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        for (i, c) in self.clusters.iter().enumerate() {
+            let cl = &c.cluster;
+            lines.push(format!("core cluster {}: {} cores x {} threads = {:.2} core-threads \
+                                (smt_factor {})",
+                               i + 1, cl.count, cl.threads_per_core, c.coresthreads,
+                               params.smt_factor));
+            lines.push(format!("  per-core speed relative to Zen 2: {:.3} (from {:?})",
+                               c.speed, c.speed_source));
+            lines.push(match c.cache_per_core {
+                Some(mb) => format!("  cache: {mb:.2} MB per core, factor {:.3}",
+                                    c.cache_factor),
+                None => "  cache: unknown, factor 1".into(),
+            });
+            let base = cl.base_clock.value()?.0;
+            lines.push(format!("  parallel: {:.2} core-threads x {base} GHz base clock \
+                                x {:.3} x {:.3} = {:.2}",
+                               c.coresthreads, c.speed, c.cache_factor, c.score));
+            lines.push(match (cl.max_boost_clock.get(), c.serial_score) {
+                (Some(boost), Some(serial_score)) => format!(
+                    "  serial: 1 core x {} GHz max boost clock x {:.3} x {:.3} = {serial_score:.2}",
+                    boost.0, c.speed, c.cache_factor),
+                _ => "  serial: max boost clock unknown".into(),
+            });
+        }
+        lines.push(format!("memory bandwidth: {:.1} GB/s, {:.2} GB/s per thread (needed {}), \
+                            factor {:.3}",
+                           self.memory_bandwidth, self.bandwidth_per_thread,
+                           params.bandwidth_per_thread_needed, self.bandwidth_factor));
+        let sum: f32 = self.clusters.iter().map(|c| c.score).sum();
+        let sockets = self.sockets;
+        lines.push(if sockets > 1 {
+            format!("parallel score: {sum:.2} x {:.3} x {sockets} sockets x {:.3} = {:.2}",
+                    self.bandwidth_factor, params.multi_socket_efficiency, self.parallel_score)
+        } else {
+            format!("parallel score: {sum:.2} x {:.3} = {:.2}",
+                    self.bandwidth_factor, self.parallel_score)
+        });
+        match self.serial_score {
+            Some(serial_score) => {
+                lines.push(format!("serial score (best core cluster): {serial_score:.2}"));
+                let p = params.parallel_fraction;
+                lines.push(format!("score: 1 / ({:.2} / {serial_score:.2} + {p:.2} / {:.2}) \
+                                    = {:.2}",
+                                   1. - p, self.parallel_score, self.score));
+            }
+            None => lines.push("score: the parallel score (parallel fraction 1)".into()),
+        }
+        lines.push(format!("job time: {} / {:.2} = {:.1} s",
+                           params.job_work, self.score, self.job_time));
+        Ok(lines)
+    }
+}
+
+/// Combined score of a job of which `parallel_fraction` runs at
+/// `parallel_score` and the rest at `serial_score`; inversely
+/// proportional to the time taken.
+pub fn amdahl(parallel_fraction: f32, serial_score: f32, parallel_score: f32) -> f32 {
+    1. / ((1. - parallel_fraction) / serial_score + parallel_fraction / parallel_score)
+}
+
+/// Saturation penalty when many threads share few memory channels.
+pub fn bandwidth_factor(params: &ProfileParams, bandwidth_per_thread: f32) -> f32 {
+    let ratio = bandwidth_per_thread / params.bandwidth_per_thread_needed;
+    if ratio >= 1. {
+        1.
+    } else {
+        ratio.powf(params.bandwidth_elasticity)
+    }
+}
+
+
+/// Speed of a core relative to a Zen 2 core at the same clock; the
+/// default for `ProfileParams.ipc`. Mostly from manufacturer claims
+/// (Zen 3 +19% over Zen 2, Zen 4 +13% over Zen 3, Zen 5 +16% over Zen
+/// 4) and reviews for Intel.
+pub const IPC: &[(Architecture, f32)] = {
+    use Architecture::*;
+    &[
+        (Zen1, 0.85),
+        (Zen2, 1.0),
+        (Zen3, 1.19),
+        (Zen4, 1.35),
+        (Zen4c, 1.35),
+        (Zen5, 1.55),
+        (Zen5c, 1.55),
+        (Skylake, 0.95),
+        (CascadeLake, 0.95),
+        (CooperLake, 0.95),
+        (IceLake, 1.1),
+        (SapphireRapids, 1.3),
+        (EmeraldRapids, 1.3),
+        (GraniteRapids, 1.35),
+        (SierraForest, 0.85),
+        (GoldenCove, 1.3),
+        (RaptorCove, 1.33),
+        (LionCove, 1.5),
+        (Gracemont, 0.85),
+        (Skymont, 1.2),
+        // Not core microarchitectures, thus not here: Infinity, and
+        // the hybrid AlderLake, RaptorLake, ArrowLake (see their
+        // `CoreCluster`s instead).
+    ]
+};
+
+pub fn relative_ipc(params: &ProfileParams, arch: &Architecture) -> Option<f32> {
+    params.ipc.get(arch).copied()
+}
+
+/// Per-core speed factor from the launch date of the CPU, relative to
+/// the first Zen 2 CPUs.
+fn launch_date_factor(params: &ProfileParams, launch_date: &Date) -> Result<f32> {
+    let zen2_launch = Date::new(2019, 7, 7)?.unixtime();
+    let launch_date_sec = launch_date.unixtime();
+    let years: f32 = ((launch_date_sec - zen2_launch) as f32) / (365.*24.*3600.);
+    let periods: f32 = years / params.doubling_period_years;
+    Ok(f32::powf(2.0, periods))
+}
+
+/// Collects the fields a score needs that are missing (or not
+/// applicable), so that all of them can be reported, not just the
+/// first one.
+#[derive(Default)]
+struct Inputs {
+    missing: Vec<String>,
+}
+
+impl Inputs {
+    fn get<'v, T>(&mut self, value: &'v Value<T>, name: impl FnOnce() -> String) -> Option<&'v T> {
+        let v = value.value().ok();
+        if v.is_none() {
+            self.missing.push(name());
+        }
+        v
+    }
+
+    /// Fails naming the missing fields, if any.
+    fn check(mut self) -> Result<()> {
+        self.missing.dedup();
+        if ! self.missing.is_empty() {
+            bail!("missing {}", self.missing.join(", "))
+        }
+        Ok(())
+    }
+}
+
+/// Per-core speed at the same clock relative to Zen 2, from the
+/// architecture if possible, from the brand or the launch date
+/// otherwise; None if the launch date is needed but missing.
+fn per_core_speed(
+    params: &ProfileParams, cpu: &CPU, cluster: &CoreCluster, inputs: &mut Inputs, prefix: &str
+) -> Result<Option<(f32, SpeedSource)>> {
+    if let Some(arch) = cluster.architecture.get() {
+        if let Some(ipc) = relative_ipc(params, arch) {
+            return Ok(Some((ipc, SpeedSource::Architecture(arch.clone()))))
+        }
+    }
+    if let Some(pl) = cpu.product_line.get() {
+        if let Some(speed) = pl.0.info().epyc_speed.get() {
+            return Ok(Some((*speed, SpeedSource::Brand(pl.0))))
+        }
+    }
+    let name = || format!("launch_date (or a known {prefix}architecture)");
+    let Some(launch_date) = inputs.get(&cpu.launch_date, name) else { return Ok(None) };
+    Ok(Some((launch_date_factor(params, launch_date)?, SpeedSource::LaunchDate)))
+}
+
+/// Cores plus additional threads (SMT) weighted by `smt_factor`.
+fn coresthreads(cluster: &CoreCluster, smt_factor: f32) -> f32 {
+    let cores = cluster.count as f32;
+    let threads = (cluster.count as u16 * cluster.threads_per_core as u16) as f32;
+    let additional_threads = threads - cores;
+
+    cores + additional_threads * smt_factor
+}
+
+
+/// A performance estimate score, higher is better, that works for
+/// comparison across CPU families and is inversely proportional to
+/// time required to compile a single Rust or C++ package on that CPU
+/// (using all of its threads as much as compilation allows, assuming
+/// use of the mold linker), i.e. simulating rebuilds of a project
+/// where a random file is changed (header file in C++ -> rebuild of
+/// many of the object files; rebuild of the lib crate and maybe also
+/// the app crate in Rust, relying on the parallel stage in LLVM for
+/// concurrency, as well as hoping somewhat for future parallelization
+/// of the frontend). This is the contribution of one core cluster,
+/// given its `per_core_speed`.
+pub fn anticipated_compilation_performance(
+    params: &ProfileParams, cluster: &CoreCluster, speed: f32
+) -> Result<f32> {
+    let coresthreads = coresthreads(cluster, params.smt_factor);
+
+    // When highly parallel, only base clock usable, right? The serial
+    // parts are covered by `anticipated_serial_performance`.
+    let base_clock = cluster.base_clock.value()?;
+
+    // XX PCIe
+
+    Ok(coresthreads * base_clock.0 * speed)
+}
+
+/// A performance estimate score for running AI inference on the CPU,
+/// on the same scale as `anticipated_compilation_performance`. The
+/// vector units are shared between the threads of a core, thus SMT is
+/// assumed to not help much (see `default_params`). This is the
+/// contribution of one core cluster, given its `per_core_speed`.
+pub fn anticipated_ai_performance(
+    params: &ProfileParams, cluster: &CoreCluster, speed: f32
+) -> Result<f32> {
+    let coresthreads = coresthreads(cluster, params.smt_factor);
+    let base_clock = cluster.base_clock.value()?;
+
+    Ok(coresthreads * base_clock.0 * speed)
+}
+
+/// Score of a single core of the cluster running alone, at max boost
+/// clock, on the same scale as `anticipated_compilation_performance`.
+pub fn anticipated_serial_performance(cluster: &CoreCluster, speed: f32) -> Result<f32> {
+    let max_boost_clock = cluster.max_boost_clock.value()?;
+
+    Ok(max_boost_clock.0 * speed)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_factors() {
+        let params = Profile::Compilation.default_params();
+        assert_eq!(bandwidth_factor(&params, 10.), 1.);
+        assert!(bandwidth_factor(&params, 1.) < bandwidth_factor(&params, 2.));
+        assert_eq!(cache_factor(&params, params.cache_reference_per_core), 1.);
+        assert!(cache_factor(&params, 9.) > 1.);
+        let no_cache = ProfileParams { cache_weight: 0., ..params };
+        assert_eq!(cache_factor(&no_cache, 9.), 1.);
+        assert_eq!(amdahl(1., 5., 100.), 100.);
+        assert_eq!(amdahl(0., 5., 100.), 5.);
+        let half = amdahl(0.5, 5., 100.);
+        assert!(5. < half && half < 10.);
+    }
+
+    #[test]
+    fn t_clusters() {
+        let cpu = CPU::test_two_clusters();
+        for profile in Profile::ALL {
+            let params = profile.default_params();
+            let perf = profile.performance(&params, &cpu).unwrap();
+            assert_eq!(perf.clusters.len(), 2);
+            let sum: f32 = perf.clusters.iter().map(|c| c.score).sum();
+            assert!((perf.parallel_score - sum * perf.bandwidth_factor).abs() < 1e-3 * sum);
+            assert!(perf.clusters.iter().all(|c| c.score > 0.));
+            // the serial part runs on the fastest core
+            let [fast, slow] = &perf.clusters[..] else { unreachable!() };
+            assert!(fast.serial_score.unwrap() > slow.serial_score.unwrap());
+            if params.parallel_fraction < 1. {
+                assert_eq!(perf.serial_score, fast.serial_score);
+            }
+            assert_eq!(perf.bandwidth_per_thread, perf.memory_bandwidth / 40.);
+        }
+    }
+
+    #[test]
+    fn t_missing_inputs() {
+        let params = Profile::Compilation.default_params();
+        let cpu = CPU {
+            architecture: Value::Missing,
+            product_line: Value::Missing,
+            launch_date: Value::Missing,
+            base_clock: Value::Missing,
+            max_boost_clock: Value::Missing,
+            memory_channels: Value::NotApplicable,
+            ..CPU::test_epyc_7543p()
+        };
+        let e = Profile::Compilation.performance(&params, &cpu).unwrap_err();
+        assert_eq!(e.to_string(), "missing launch_date (or a known architecture), base_clock, \
+                                   max_boost_clock, memory_channels");
+        let e = Profile::Compilation.performance_with_sockets(&params, &cpu, 2).unwrap_err();
+        assert!(e.to_string().contains("supports at most 1 socket(s)"));
+
+        // the max boost clock is only needed for a serial part
+        let cpu = CPU { max_boost_clock: Value::Missing, ..CPU::test_epyc_7543p() };
+        let ai = Profile::AI.default_params();
+        assert_eq!(ai.parallel_fraction, 1.);
+        let perf = Profile::AI.performance(&ai, &cpu).unwrap();
+        assert_eq!(perf.serial_score, None);
+        assert_eq!(perf.score, perf.parallel_score);
+        assert_eq!(perf.job_time, ai.job_work / perf.parallel_score);
+        let parallel = ProfileParams { parallel_fraction: 1., ..params.clone() };
+        let perf = Profile::Compilation.performance(&parallel, &cpu).unwrap();
+        assert_eq!(perf.serial_score, None);
+        assert_eq!(perf.score, perf.parallel_score);
+        assert!(params.parallel_fraction < 1.);
+        let e = Profile::Compilation.performance(&params, &cpu).unwrap_err();
+        assert_eq!(e.to_string(), "missing max_boost_clock");
+    }
+
+    #[test]
+    fn t_sockets() {
+        let cpu = CPU { name: "AMD EPYC 7543".into(), max_sockets: 2.into(),
+                        ..CPU::test_epyc_7543p() };
+        for profile in Profile::ALL {
+            let params = profile.default_params();
+            let one = profile.performance_with_sockets(&params, &cpu, 1).unwrap();
+            let two = profile.performance_with_sockets(&params, &cpu, 2).unwrap();
+            assert_eq!(two.sockets, 2);
+            assert_eq!(two.memory_bandwidth, 2. * one.memory_bandwidth);
+            assert_eq!(two.bandwidth_factor, one.bandwidth_factor);
+            let expected = 2. * params.multi_socket_efficiency * one.parallel_score;
+            assert!((two.parallel_score - expected).abs() < 1e-3 * expected);
+            // the serial part runs on one core either way
+            assert_eq!(two.serial_score, one.serial_score);
+            assert!(two.score > one.score);
+
+            let half = ProfileParams { multi_socket_efficiency: 0.5, ..params.clone() };
+            let two = profile.performance_with_sockets(&half, &cpu, 2).unwrap();
+            assert!((two.parallel_score - one.parallel_score).abs() < 1e-3 * one.parallel_score);
+            assert!(profile.performance_with_sockets(&params, &cpu, 3).is_err());
+        }
+        let config = ProfileConfig::new(Profile::Compilation);
+        assert_eq!(config.platform_chf(1), 0.);
+        assert_eq!(config.platform_chf(2), config.multi_socket_premium_chf);
+    }
+
+    #[test]
+    fn t_explain() {
+        let params = Profile::Compilation.default_params();
+        let cpu = CPU::test_two_clusters();
+        let perf = Profile::Compilation.performance(&params, &cpu).unwrap();
+        let lines = perf.explain(&params).unwrap();
+        let starting = |prefix: &str| lines.iter().filter(|l| l.starts_with(prefix)).count();
+        assert_eq!(starting("core cluster "), 2);
+        assert_eq!(starting("  serial: 1 core x "), 2);
+        assert_eq!(lines[0], "core cluster 1: 8 cores x 2 threads = 10.40 core-threads \
+                              (smt_factor 0.3)");
+        let sum: f32 = perf.clusters.iter().map(|c| c.score).sum();
+        assert!(lines.contains(&format!("parallel score: {sum:.2} x {:.3} = {:.2}",
+                                        perf.bandwidth_factor, perf.parallel_score)));
+        assert!(lines.contains(&format!("serial score (best core cluster): {:.2}",
+                                        perf.clusters[0].serial_score.unwrap())));
+        assert_eq!(lines.last().unwrap(),
+                   &format!("job time: {} / {:.2} = {:.1} s",
+                            params.job_work, perf.score, perf.job_time));
+
+        let cpu = CPU { name: "AMD EPYC 7543".into(), max_sockets: 2.into(),
+                        max_boost_clock: Value::Missing, ..CPU::test_epyc_7543p() };
+        let parallel = ProfileParams { parallel_fraction: 1., ..params };
+        let perf = Profile::Compilation.performance_with_sockets(&parallel, &cpu, 2).unwrap();
+        let lines = perf.explain(&parallel).unwrap();
+        assert!(lines.contains(&"  serial: max boost clock unknown".into()));
+        assert!(lines.iter().any(|l| l.starts_with("parallel score: ")
+                                 && l.contains(" x 2 sockets x ")));
+        assert!(lines.contains(&"score: the parallel score (parallel fraction 1)".into()));
+    }
+
+    #[test]
+    fn t_profile_config() {
+        let json = |c: &ProfileConfig| serde_json::to_value(c).unwrap();
+        let defaults = ProfileConfig::new(Profile::AI);
+        assert_eq!(json(&ProfileConfig::from_json(r#"{"profile": "AI"}"#).unwrap()),
+                   json(&defaults));
+        let c = ProfileConfig::from_json(
+            r#"{"profile": "AI", "params": {"smt_factor": 0.1, "ipc": {"Zen2": 1.1}}}"#
+        ).unwrap();
+        assert_eq!(c.params.smt_factor, 0.1);
+        assert_eq!(c.params.ipc[&Architecture::Zen2], 1.1);
+        assert_eq!(c.params.ipc[&Architecture::Zen3], defaults.params.ipc[&Architecture::Zen3]);
+        assert_eq!(c.params.cache_weight, defaults.params.cache_weight);
+        assert!(ProfileConfig::from_json(r#"{"profile": "AI", "params": {"smt": 0.1}}"#)
+                .is_err());
+        assert!(ProfileConfig::from_json(r#"{"params": {}}"#).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! Basic statistics, for comparing estimates with measurements, and
+//! fitting model parameters to them.
+
+use anyhow::{Result, bail};
+
+pub fn mean(xs: &[f32]) -> f32 {
+    xs.iter().sum::<f32>() / xs.len() as f32
+}
+
+/// Pearson correlation coefficient; None if there are fewer than 2
+/// points or one of the variables is constant.
+pub fn pearson(xs: &[f32], ys: &[f32]) -> Option<f32> {
+    assert_eq!(xs.len(), ys.len());
+    if xs.len() < 2 {
+        return None
+    }
+    let (mx, my) = (mean(xs), mean(ys));
+    let mut sxy = 0.;
+    let mut sxx = 0.;
+    let mut syy = 0.;
+    for (x, y) in xs.iter().zip(ys) {
+        sxy += (x - mx) * (y - my);
+        sxx += (x - mx) * (x - mx);
+        syy += (y - my) * (y - my);
+    }
+    if sxx == 0. || syy == 0. {
+        return None
+    }
+    Some(sxy / (sxx * syy).sqrt())
+}
+
+/// The factor k for which `measured ≈ k * predicted` fits best in log
+/// space (i.e. relative errors count, not absolute ones): the
+/// geometric mean of measured / predicted.
+pub fn log_scale(predicted: &[f32], measured: &[f32]) -> f32 {
+    let logs: Vec<f32> = predicted.iter().zip(measured)
+        .map(|(p, m)| (m / p).ln())
+        .collect();
+    mean(&logs).exp()
+}
+
+/// Solve `a x = b` by Gaussian elimination with partial pivoting;
+/// None if `a` is singular.
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-300 {
+            return None
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let f = a[row][col] / pivot_row[col];
+            for (x, p) in a[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *x -= f * p;
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+pub fn sum_of_squares(r: &[f64]) -> f64 {
+    r.iter().map(|x| x * x).sum()
+}
+
+/// Minimise the sum of squares of `residuals(p)` by
+/// Levenberg-Marquardt with numerical derivatives, starting from
+/// `p0`, keeping each parameter within its `bounds`. Returns the
+/// parameters and the sum of squares reached. Parameters that have no
+/// influence on the residuals stay unchanged. `residuals` must return
+/// the same number of residuals, in the same order, for any `p`.
+pub fn least_squares(
+    p0: &[f64],
+    bounds: &[(f64, f64)],
+    residuals: impl Fn(&[f64]) -> Result<Vec<f64>>,
+) -> Result<(Vec<f64>, f64)> {
+    let n = p0.len();
+    let clamp = |p: Vec<f64>| -> Vec<f64> {
+        p.iter().zip(bounds).map(|(x, (lo, hi))| x.clamp(*lo, *hi)).collect()
+    };
+    let mut p = clamp(p0.to_vec());
+    let mut r = residuals(&p)?;
+    let mut ssr = sum_of_squares(&r);
+    let mut lambda = 1e-3;
+    for _iteration in 0..200 {
+        // Jacobian, by forward (or, at the upper bound, backward)
+        // differences
+        let mut jac = vec![vec![0.; n]; r.len()];
+        for k in 0..n {
+            let mut h = 1e-4 * p[k].abs().max(1e-2);
+            if p[k] + h > bounds[k].1 {
+                h = -h;
+            }
+            let mut q = p.clone();
+            q[k] += h;
+            let rq = residuals(&q)?;
+            if rq.len() != r.len() {
+                bail!("the number of residuals changed from {} to {}", r.len(), rq.len())
+            }
+            for (row, (a, b)) in jac.iter_mut().zip(rq.iter().zip(&r)) {
+                row[k] = (a - b) / h;
+            }
+        }
+        let mut jtj = vec![vec![0.; n]; n];
+        let mut jtr = vec![0.; n];
+        for (row, ri) in jac.iter().zip(&r) {
+            for k in 0..n {
+                jtr[k] += row[k] * ri;
+                for l in 0..n {
+                    jtj[k][l] += row[k] * row[l];
+                }
+            }
+        }
+        let mut improved = false;
+        while lambda < 1e12 {
+            let mut a = jtj.clone();
+            for (k, row) in a.iter_mut().enumerate() {
+                // 1 for parameters without influence, to keep them
+                // unchanged instead of making `a` singular
+                row[k] += lambda * if jtj[k][k] > 0. { jtj[k][k] } else { 1. };
+            }
+            let b: Vec<f64> = jtr.iter().map(|x| -x).collect();
+            if let Some(delta) = solve(a, b) {
+                let q = clamp(p.iter().zip(&delta).map(|(x, d)| x + d).collect());
+                let rq = residuals(&q)?;
+                if rq.len() != r.len() {
+                    bail!("the number of residuals changed from {} to {}", r.len(), rq.len())
+                }
+                let s = sum_of_squares(&rq);
+                if s < ssr {
+                    let converged = ssr - s < 1e-12 * ssr.max(1e-12);
+                    p = q;
+                    r = rq;
+                    ssr = s;
+                    lambda /= 10.;
+                    improved = ! converged;
+                    break
+                }
+            }
+            lambda *= 10.;
+        }
+        if ! improved {
+            break
+        }
+    }
+    Ok((p, ssr))
+}
+
+/// `q` (0..=1) quantile of `sorted`, by linear interpolation.
+pub fn quantile(sorted: &[f32], q: f32) -> f32 {
+    let pos = q * (sorted.len() - 1) as f32;
+    let (i, frac) = (pos.floor() as usize, pos.fract());
+    match sorted.get(i + 1) {
+        Some(next) => sorted[i] + (next - sorted[i]) * frac,
+        None => sorted[i],
+    }
+}
+
+/// Pseudo random numbers (SplitMix64), for reproducible Monte Carlo
+/// runs without dependencies.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in [0, 1).
+    pub fn uniform(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniformly distributed factor in [1 - range, 1 + range).
+    pub fn factor(&mut self, range: f32) -> f32 {
+        1. + range * (2. * self.uniform() - 1.)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_stats() {
+        let xs = [1., 2., 3., 4.];
+        assert_eq!(mean(&xs), 2.5);
+        assert!((pearson(&xs, &[2., 4., 6., 8.]).unwrap() - 1.).abs() < 1e-6);
+        assert!((pearson(&xs, &[8., 6., 4., 2.]).unwrap() + 1.).abs() < 1e-6);
+        assert_eq!(pearson(&xs, &[1., 1., 1., 1.]), None);
+        assert!((log_scale(&xs, &[3., 6., 9., 12.]) - 3.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn t_random() {
+        assert_eq!(quantile(&[1., 2., 3., 4., 5.], 0.5), 3.);
+        assert_eq!(quantile(&[1., 2.], 0.25), 1.25);
+        assert_eq!(quantile(&[1., 2.], 1.), 2.);
+        let mut rng = Rng::new(1);
+        let xs: Vec<f32> = (0..1000).map(|_| rng.factor(0.1)).collect();
+        assert!(xs.iter().all(|x| (0.9..1.1).contains(x)));
+        assert!((mean(&xs) - 1.).abs() < 0.01);
+        assert_eq!(Rng::new(1).next_u64(), Rng::new(1).next_u64());
+    }
+
+    #[test]
+    fn t_least_squares() {
+        assert_eq!(solve(vec![vec![2., 1.], vec![1., 3.]], vec![3., 5.]),
+                   Some(vec![0.8, 1.4]));
+        assert_eq!(solve(vec![vec![1., 2.], vec![2., 4.]], vec![1., 2.]), None);
+
+        // y = 3 x^0.5, with the third parameter having no influence
+        let xs = [1., 2., 4., 8., 16.];
+        let (p, ssr) = least_squares(
+            &[1., 1., 7.], &[(0., 10.), (0., 0.8), (0., 10.)],
+            |p| Ok(xs.iter().map(|x: &f64| p[0] * x.powf(p[1]) - 3. * x.sqrt()).collect())
+        ).unwrap();
+        assert!((p[0] - 3.).abs() < 1e-4, "{p:?}");
+        assert!((p[1] - 0.5).abs() < 1e-4, "{p:?}");
+        assert_eq!(p[2], 7.);
+        assert!(ssr < 1e-8);
+
+        let changing = least_squares(
+            &[1.], &[(0., 10.)], |p| Ok(vec![p[0] - 2.; if p[0] > 1. { 1 } else { 2 }]));
+        assert!(changing.is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Tables owning their rows, indexed by primary key, and foreign key
+//! relations between them.
+
+use std::{collections::HashMap, fmt::Debug};
+
+use anyhow::{bail, Result};
+
+use crate::{index::PrimaryKey, report::{Report, Severity}, fuzzy::did_you_mean};
+
+
+pub struct Table<T: PrimaryKey> {
+    name: &'static str,
+    rows: Vec<T>,
+    index: HashMap<T::Key, usize>,
+}
+
+impl<T: PrimaryKey + Debug> Table<T> {
+    /// Builds the primary index, checking that there are no
+    /// duplicate keys (reporting all of them).
+    pub fn new(name: &'static str, rows: Vec<T>) -> Result<Self> {
+        let mut index = HashMap::new();
+        let mut duplicates = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            if let Some(old) = index.insert(row.primary_key().clone(), i) {
+                duplicates.push(format!("duplicate primary key {:?} used in item {row:#?} \
+                                         and previously {:#?}",
+                                        row.primary_key(), rows[old]));
+            }
+        }
+        if ! duplicates.is_empty() {
+            bail!("table {name}: {}", duplicates.join("\n"));
+        }
+        Ok(Self { name, rows, index })
+    }
+}
+
+impl<T: PrimaryKey> Table<T> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn rows(&self) -> &[T] {
+        &self.rows
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn get(&self, key: &T::Key) -> Option<&T> {
+        self.index.get(key).map(|i| &self.rows[*i])
+    }
+
+    pub fn contains_key(&self, key: &T::Key) -> bool {
+        self.index.contains_key(key)
+    }
+}
+
+
+/// Declaration of a foreign key from rows in table `T` to the primary
+/// key of table `U`, with the expected cardinality.
+pub struct RelationDef<T: PrimaryKey, U: PrimaryKey> {
+    /// E.g. "SoldAt.article_name -> CPU.name"
+    pub name: &'static str,
+    pub key: fn(&T) -> &U::Key,
+    /// How bad it is if a row in `T` references a nonexisting row in
+    /// `U`.
+    pub broken_reference: Severity,
+    /// Minimum number of rows in `T` that every row in `U` should be
+    /// referenced by, and how bad it is if that's not the case.
+    pub min_referenced_by: Option<(usize, Severity)>,
+}
+
+/// A declared foreign key relation, with a reverse index (rows in `T`
+/// referencing a given key).
+pub struct Relation<T: PrimaryKey, U: PrimaryKey> {
+    pub def: RelationDef<T, U>,
+    reverse: HashMap<U::Key, Vec<usize>>,
+}
+
+impl<T: PrimaryKey, U: PrimaryKey> Relation<T, U> {
+    pub fn new(def: RelationDef<T, U>, from: &Table<T>) -> Self {
+        let mut reverse: HashMap<U::Key, Vec<usize>> = HashMap::new();
+        for (i, row) in from.rows.iter().enumerate() {
+            reverse.entry((def.key)(row).clone()).or_default().push(i);
+        }
+        Self { def, reverse }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.def.name
+    }
+
+    pub fn key<'t>(&self, row: &'t T) -> &'t U::Key {
+        (self.def.key)(row)
+    }
+
+    /// Check all rows in `from` and `to` against the declaration,
+    /// adding all violations to `report`. Broken references come with
+    /// suggestions of similar existing keys.
+    pub fn check(&self, from: &Table<T>, to: &Table<U>, report: &mut Report)
+    where U::Key: AsRef<str>
+    {
+        let name = self.def.name;
+        for row in &from.rows {
+            let k = self.key(row);
+            if ! to.contains_key(k) {
+                report.push(self.def.broken_reference, name, format!(
+                    "{} {:?}: value {k:?} for foreign key {name} does not exist{}",
+                    from.name, row.primary_key(),
+                    did_you_mean(k.as_ref(), to.index.keys().map(|k| k.as_ref()))));
+            }
+        }
+        if let Some((min, severity)) = self.def.min_referenced_by {
+            for row in &to.rows {
+                let n = self.reverse.get(row.primary_key()).map(|v| v.len()).unwrap_or(0);
+                if n < min {
+                    report.push(severity, name, format!(
+                        "{} {:?} is referenced by {n} {} row(s), expected at least {min}",
+                        to.name, row.primary_key(), from.name));
+                }
+            }
+        }
+    }
+
+    /// The row referenced by `row`.
+    pub fn target<'u>(&self, row: &T, to: &'u Table<U>) -> Option<&'u U> {
+        to.get(self.key(row))
+    }
+
+    /// The rows in `from` referencing `target`.
+    pub fn sources<'s, 't: 's>(
+        &'s self, target: &U, from: &'t Table<T>
+    ) -> impl Iterator<Item = &'t T> + 's {
+        let is = self.reverse.get(target.primary_key()).map(|v| &v[..]).unwrap_or(&[]);
+        is.iter().map(|i| &from.rows[*i])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Row(&'static str, &'static str);
+
+    impl PrimaryKey for Row {
+        type Key = &'static str;
+        fn primary_key(&self) -> &&'static str {
+            &self.0
+        }
+    }
+
+    #[derive(Debug)]
+    struct Ref(&'static str, &'static str);
+
+    impl PrimaryKey for Ref {
+        type Key = &'static str;
+        fn primary_key(&self) -> &&'static str {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn t_table() {
+        assert!(Table::new("Row", vec![Row("1", "a"), Row("1", "b")]).is_err());
+        let rows = Table::new("Row", vec![Row("1", "a"), Row("2", "b")]).unwrap();
+        let refs = Table::new("Ref", vec![Ref("x", "1"), Ref("y", "1"), Ref("z", "3")]).unwrap();
+        let rel = Relation::new(RelationDef {
+            name: "Ref.1 -> Row.0",
+            key: |r: &Ref| &r.1,
+            broken_reference: Severity::Error,
+            min_referenced_by: Some((1, Severity::Warning)),
+        }, &refs);
+        let mut report = Report::default();
+        rel.check(&refs, &rows, &mut report);
+        assert_eq!(report.errors().count(), 1); // "z" -> 3
+        assert_eq!(report.warnings().count(), 1); // 2 unreferenced
+        assert_eq!(rel.target(&refs.rows()[0], &rows).unwrap().1, "a");
+        let sources: Vec<_> = rel.sources(&rows.rows()[0], &refs).map(|r| r.0).collect();
+        assert_eq!(sources, ["x", "y"]);
+        assert_eq!(rel.sources(&rows.rows()[1], &refs).count(), 0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Total cost of ownership: the purchase price plus the electricity
+//! used over the service life.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::types::CPU;
+
+
+/// Assumptions about how the CPU will be run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TcoParams {
+    pub electricity_chf_per_kwh: f32,
+    /// Share of the time (0..=1) the CPU is busy, drawing its TDP
+    pub utilisation: f32,
+    /// Draw of an idle CPU relative to its TDP
+    pub idle_power_fraction: f32,
+    /// Watts drawn all the time by the rest of the machine (board,
+    /// memory, disks, fans, power supply losses)
+    pub platform_overhead: f32,
+    pub service_life_years: f32,
+}
+
+impl Default for TcoParams {
+    fn default() -> Self {
+        TcoParams {
+            // Swiss household tariffs are around 0.3 CHF/kWh
+            electricity_chf_per_kwh: 0.3,
+            // a build server, busy during the working day
+            utilisation: 0.3,
+            idle_power_fraction: 0.2,
+            platform_overhead: 50.,
+            service_life_years: 5.,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Tco {
+    pub purchase_chf: f32,
+    /// Watts, averaged over busy and idle times
+    pub average_power: f32,
+    /// Over the service life
+    pub energy_kwh: f32,
+    pub energy_chf: f32,
+    pub total_chf: f32,
+}
+
+/// Cost of buying `sockets` of `cpu` for `purchase_chf` in total
+/// and running them as assumed in `params`; the TDP is taken as the
+/// draw when busy.
+pub fn tco(params: &TcoParams, cpu: &CPU, sockets: u8, purchase_chf: f32) -> Result<Tco> {
+    let Ok(tdp) = cpu.tdp.value() else { bail!("missing tdp") };
+    let tdp = tdp.0 as f32 * sockets as f32;
+    let u = params.utilisation;
+    let average_power =
+        params.platform_overhead + tdp * (u + (1. - u) * params.idle_power_fraction);
+    let hours = params.service_life_years * 365.25 * 24.;
+    let energy_kwh = average_power * hours / 1000.;
+    let energy_chf = energy_kwh * params.electricity_chf_per_kwh;
+    Ok(Tco {
+        purchase_chf,
+        average_power,
+        energy_kwh,
+        energy_chf,
+        total_chf: purchase_chf + energy_chf,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::value::Value;
+    use super::*;
+
+    #[test]
+    fn t_tco() {
+        let cpu = CPU::test_epyc_7543p(); // 225 W
+        let busy = TcoParams {
+            electricity_chf_per_kwh: 0.3,
+            utilisation: 1.,
+            idle_power_fraction: 0.,
+            platform_overhead: 0.,
+            service_life_years: 1.,
+        };
+        let t = tco(&busy, &cpu, 1, 1000.).unwrap();
+        assert_eq!(t.average_power, 225.);
+        // a year is 8766 hours
+        assert!((t.energy_kwh - 225. * 8.766).abs() < 0.01);
+        assert!((t.energy_chf - 225. * 8.766 * 0.3).abs() < 0.01);
+        assert_eq!(t.total_chf, 1000. + t.energy_chf);
+
+        // the energy costs accrue over the service life, the purchase
+        // is paid once
+        let five_years = tco(&TcoParams { service_life_years: 5., ..busy.clone() },
+                             &cpu, 1, 1000.).unwrap();
+        assert!((five_years.energy_chf - 5. * t.energy_chf).abs() < 0.1);
+        assert_eq!(five_years.purchase_chf, 1000.);
+
+        // idle for 70% of the time at 20% of the TDP, plus the platform
+        let t = tco(&TcoParams::default(), &cpu, 1, 1000.).unwrap();
+        assert!((t.average_power - (50. + 225. * (0.3 + 0.7 * 0.2))).abs() < 1e-3);
+        let two = tco(&TcoParams::default(), &cpu, 2, 2000.).unwrap();
+        assert!((two.average_power - (50. + 2. * 225. * (0.3 + 0.7 * 0.2))).abs() < 1e-3);
+
+        let no_tdp = CPU { tdp: Value::Missing, ..CPU::test_epyc_7543p() };
+        assert_eq!(tco(&busy, &no_tdp, 1, 1000.).unwrap_err().to_string(), "missing tdp");
+    }
+}
@@ -7,15 +7,17 @@ use crate::{value::Value, date::Date, index::PrimaryKey};
 use crate::{def_name_type, set};
 
 def_name_type!{ArticleName}
+def_name_type!{BenchmarkName}
+def_name_type!{ShopName}
 
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MemoryType {
     DDR4,
     DDR5,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MemorySubtype {
     UDIMM,
     RDIMM,
@@ -64,6 +66,8 @@ pub enum CPUSocket {
     // AMD:
     AM4,
     AM5,
+    TR4,
+    STRX4, // really sTRX4
     SWRX8, // really sWRX8
     SP3,
     SP5, // LGA-6096
@@ -71,7 +75,14 @@ pub enum CPUSocket {
     STR5, // really sTR5
 
     // Intel:
-    FCLGA3647,
+    FCLGA1700, // Alder Lake, Raptor Lake
+    FCLGA1851, // Arrow Lake
+    FCLGA2066,
+    FCLGA3647, // Skylake-SP, Cascade Lake
+    FCLGA4189, // Ice Lake-SP, Cooper Lake
+    FCLGA4677, // Sapphire Rapids, Emerald Rapids
+    FCLGA4710, // Xeon 6 (6700 series)
+    FCLGA7529, // Xeon 6 (6900 series)
 }
 
 impl TryFrom<&str> for CPUSocket {
@@ -87,7 +98,17 @@ impl TryFrom<&str> for CPUSocket {
             "SP5" => Ok(SP5),
             "SP6" => Ok(SP6),
             "sTR5" => Ok(STR5),
-            "FCLGA3647" => Ok(FCLGA3647),
+            "TR4" => Ok(TR4),
+            "sTRX4" => Ok(STRX4),
+            // Intel's ARK says "FCLGA3647", shops often "LGA 3647"
+            "FCLGA1700" | "LGA1700" | "LGA 1700" => Ok(FCLGA1700),
+            "FCLGA1851" | "LGA1851" | "LGA 1851" => Ok(FCLGA1851),
+            "FCLGA2066" | "LGA2066" | "LGA 2066" => Ok(FCLGA2066),
+            "FCLGA3647" | "LGA3647" | "LGA 3647" => Ok(FCLGA3647),
+            "FCLGA4189" | "LGA4189" | "LGA 4189" => Ok(FCLGA4189),
+            "FCLGA4677" | "LGA4677" | "LGA 4677" => Ok(FCLGA4677),
+            "FCLGA4710" | "LGA4710" | "LGA 4710" => Ok(FCLGA4710),
+            "FCLGA7529" | "LGA7529" | "LGA 7529" => Ok(FCLGA7529),
             _ => bail!("invalid CPU Socket name {value:?}")
         }
     }
@@ -107,10 +128,11 @@ impl TryFrom<&str> for Value<CPUSocket> {
 pub enum GraphicsModel {
     None, // Discrete Graphics Card Required
     Radeon,
+    IntelUHD, // "Intel® UHD Graphics 770"
 }
 
 
-#[derive(Hash, PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Serialize, Deserialize)]
 pub enum Architecture {
     Zen1, // or just "Zen"? But that would confuse us users now.
     Zen2,
@@ -118,7 +140,29 @@ pub enum Architecture {
     Zen4,
     Zen4c,
     Zen5,
+    Zen5c,
     Infinity,
+
+    // Intel, by code name ("Products formerly Cascade Lake" in ARK):
+    Skylake,
+    CascadeLake,
+    CooperLake,
+    IceLake,
+    SapphireRapids,
+    EmeraldRapids,
+    GraniteRapids,
+    SierraForest,
+    AlderLake,
+    RaptorLake,
+    ArrowLake,
+
+    // Intel core microarchitectures, for the `CoreCluster`s of hybrid
+    // designs:
+    GoldenCove, // Alder Lake P-cores
+    RaptorCove, // Raptor Lake P-cores
+    LionCove, // Arrow Lake P-cores
+    Gracemont, // Alder/Raptor Lake E-cores
+    Skymont, // Arrow Lake E-cores
 }
 
 impl TryFrom<&str> for Architecture {
@@ -132,8 +176,30 @@ impl TryFrom<&str> for Architecture {
             "Zen 4" => Ok(Zen4),
             "Zen 4c" => Ok(Zen4c),
             "Zen 5" => Ok(Zen5),
+            "Zen 5c" => Ok(Zen5c),
             "AMD Infinity Architecture" => Ok(Infinity),
-            _ => bail!("invalid Architecture {value:?}")
+            _ => {
+                let codename = value.strip_prefix("Products formerly ").unwrap_or(value);
+                match codename {
+                    "Skylake" => Ok(Skylake),
+                    "Cascade Lake" => Ok(CascadeLake),
+                    "Cooper Lake" => Ok(CooperLake),
+                    "Ice Lake" => Ok(IceLake),
+                    "Sapphire Rapids" => Ok(SapphireRapids),
+                    "Emerald Rapids" => Ok(EmeraldRapids),
+                    "Granite Rapids" => Ok(GraniteRapids),
+                    "Sierra Forest" => Ok(SierraForest),
+                    "Alder Lake" => Ok(AlderLake),
+                    "Raptor Lake" => Ok(RaptorLake),
+                    "Arrow Lake" => Ok(ArrowLake),
+                    "Golden Cove" => Ok(GoldenCove),
+                    "Raptor Cove" => Ok(RaptorCove),
+                    "Lion Cove" => Ok(LionCove),
+                    "Gracemont" => Ok(Gracemont),
+                    "Skymont" => Ok(Skymont),
+                    _ => bail!("invalid Architecture {value:?}")
+                }
+            }
         }
     }
 }
@@ -147,6 +213,19 @@ impl TryFrom<&str> for Value<Architecture> {
 }
 
 
+/// A group of identical cores in a CPU, e.g. the P-cores of an Intel
+/// hybrid design, or the Zen 5c cores in a Zen 5 / Zen 5c mix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreCluster {
+    pub count: u8,
+    pub threads_per_core: u8,
+    pub architecture: Value<Architecture>,
+    pub base_clock: Value<GHz>,
+    pub max_boost_clock: Value<GHz>,
+    pub l2cache: Value<Bytes>, // total over the cores in the cluster
+    pub l3cache: Value<Bytes>, // as accessible to the cluster, may be shared with other clusters
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CPU {
     pub name: ArticleName, // AMD Ryzen™ 9 5950X
@@ -155,8 +234,14 @@ pub struct CPU {
     pub product_line: Value<ProductLine>, // "AMD Ryzen™ 9 Desktop Processors"
     pub architecture: Value<Architecture>,
     pub desc: String, // optional
-    pub cores: Value<u8>,
+    pub cores: Value<u8>, // "Total Cores" for Intel
     pub threads: Value<u8>,
+    /// For CPUs with different kinds of cores (Intel P-cores and
+    /// E-cores, AMD Zen 5 and Zen 5c), which together add up to
+    /// `cores` and `threads`. `NotApplicable` for CPUs with only one
+    /// kind of core, which is then described by the fields here in
+    /// `CPU`; see `clusters`.
+    pub core_clusters: Value<Vec<CoreCluster>>,
     pub l1cache: Value<Bytes>,
     pub l2cache: Value<Bytes>,
     pub l3cache: Value<Bytes>,
@@ -166,6 +251,10 @@ pub struct CPU {
     pub cooler: Value<CoolerType>,
     pub launch_date: Value<Date>,
     pub cpu_socket: Value<CPUSocket>,
+    /// How many of these CPUs can share a machine: 2 for the
+    /// dual-socket ("2P") EPYC and Xeon models, 1 for the EPYC "P"
+    /// models and desktop CPUs
+    pub max_sockets: Value<u8>,
     pub memory_channels: Value<u8>,
     pub pci_express_version: Value<ExtensionBus>,
     pub system_memory_type: Value<MemoryType>,
@@ -179,28 +268,233 @@ pub struct CPU {
     pub amd_enhanced_virus_protection_nx_bit: Value<bool>, // just nx_bit  ? 
 }
 
-impl PrimaryKey<ArticleName> for CPU {
+impl CPU {
+    /// Theoretical memory bandwidth in GB/s, from the number of
+    /// channels and the transfer rate (8 bytes per transfer).
+    pub fn memory_bandwidth(&self) -> anyhow::Result<f32> {
+        let channels = *self.memory_channels.value()? as f32;
+        let mt_per_sec = self.system_memory_specification.value()?.0 as f32;
+        Ok(channels * mt_per_sec * 8. / 1000.)
+    }
+
+    /// The core clusters of the CPU: `core_clusters` if given (which
+    /// must not be empty, nor contradict `cores` and `threads` where
+    /// these are known), a single cluster derived from the CPU-wide
+    /// fields otherwise.
+    pub fn clusters(&self) -> anyhow::Result<Vec<CoreCluster>> {
+        match &self.core_clusters {
+            Value::NotApplicable => {
+                let cores = *self.cores.value()?;
+                let threads = *self.threads.value()?;
+                if cores == 0 || threads % cores != 0 {
+                    bail!("{threads} threads can't be evenly distributed over {cores} cores")
+                }
+                Ok(vec![CoreCluster {
+                    count: cores,
+                    threads_per_core: threads / cores,
+                    architecture: self.architecture.clone(),
+                    base_clock: self.base_clock.clone(),
+                    max_boost_clock: self.max_boost_clock.clone(),
+                    l2cache: self.l2cache.clone(),
+                    l3cache: self.l3cache.clone(),
+                }])
+            }
+            clusters => {
+                let clusters = clusters.value()?;
+                if clusters.is_empty() {
+                    bail!("core_clusters is empty")
+                }
+                let cores: u32 = clusters.iter().map(|c| c.count as u32).sum();
+                let threads: u32 = clusters.iter()
+                    .map(|c| c.count as u32 * c.threads_per_core as u32).sum();
+                for (what, total, sum) in [("cores", &self.cores, cores),
+                                           ("threads", &self.threads, threads)] {
+                    if let Some(total) = total.get() {
+                        if *total as u32 != sum {
+                            bail!("{what} is {total}, but the core clusters add up to {sum}")
+                        }
+                    }
+                }
+                Ok(clusters.clone())
+            }
+        }
+    }
+
+    /// The socket counts worth scoring (1, 2, 4, 8 up to
+    /// `max_sockets`); just 1 if `max_sockets` is unknown.
+    pub fn socket_configurations(&self) -> Vec<u8> {
+        let max = self.max_sockets.get().copied().unwrap_or(1);
+        [1, 2, 4, 8].into_iter().filter(|n| *n <= max.max(1)).collect()
+    }
+}
+
+#[cfg(test)]
+impl CPU {
+    /// A complete record none of the lint rules objects to, for tests
+    /// to modify.
+    pub fn test_epyc_7543p() -> CPU {
+        use Value::{Missing, NotApplicable};
+        CPU {
+            name: "AMD EPYC 7543P".into(),
+            url: "https://www.amd.com/en/products/cpu/amd-epyc-7543p".into(),
+            market_segment: MarketSegment::Server.into(),
+            product_line: ProductLine(Brand::EPYC7003, Usage::Server.into()).into(),
+            architecture: Architecture::Zen3.into(),
+            desc: "".into(),
+            cores: 32.into(),
+            threads: 64.into(),
+            core_clusters: NotApplicable,
+            l1cache: Bytes::KB(2048).into(),
+            l2cache: Bytes::MB(16).into(),
+            l3cache: Bytes::MB(256).into(),
+            tdp: Watt(225).into(),
+            base_clock: GHz(2.8).into(),
+            max_boost_clock: GHz(3.7).into(),
+            cooler: NotApplicable,
+            launch_date: Date::new(2021, 3, 15).unwrap().into(),
+            cpu_socket: CPUSocket::SP3.into(),
+            max_sockets: 1.into(),
+            memory_channels: 8.into(),
+            pci_express_version: ExtensionBus::PCIe(4.0, 128.into()).into(),
+            system_memory_type: MemoryType::DDR4.into(),
+            system_memory_subtype: MemorySubtype::RDIMM.into(),
+            system_memory_specification: MTperSec(3200).into(),
+            ecc_support: true.into(),
+            graphics_model: NotApplicable,
+            pcie_dma_security: Missing,
+            usb_dma_security: Missing,
+            amd_enhanced_virus_protection_nx_bit: Missing,
+        }
+    }
+
+    /// `test_epyc_7543p` split into two core clusters: 8 fast cores
+    /// with SMT and 24 slower ones without.
+    pub fn test_two_clusters() -> CPU {
+        let cluster = |count, threads_per_core, base, boost| CoreCluster {
+            count,
+            threads_per_core,
+            architecture: Architecture::Zen3.into(),
+            base_clock: GHz(base).into(),
+            max_boost_clock: GHz(boost).into(),
+            l2cache: Bytes::MB(count as u32 / 2).into(),
+            l3cache: Bytes::MB(256).into(),
+        };
+        CPU {
+            threads: 40.into(),
+            core_clusters: vec![cluster(8, 2, 3.2, 4.5), cluster(24, 1, 2.0, 3.0)].into(),
+            ..CPU::test_epyc_7543p()
+        }
+    }
+}
+
+impl PrimaryKey for CPU {
+    type Key = ArticleName;
     fn primary_key(&self) -> &ArticleName {
         &self.name
     }
 }
 
 
+/// ISO 3166-1 alpha-2 codes, as far as needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Country {
+    CH,
+    DE,
+    AT,
+    FR,
+    IT,
+}
+
+impl Country {
+    pub fn is_eu(self) -> bool {
+        match self {
+            Country::CH => false,
+            Country::DE | Country::AT | Country::FR | Country::IT => true,
+        }
+    }
+}
+
+impl TryFrom<&str> for Country {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        use Country::*;
+        match &*value.to_uppercase() {
+            "CH" => Ok(CH),
+            "DE" => Ok(DE),
+            "AT" => Ok(AT),
+            "FR" => Ok(FR),
+            "IT" => Ok(IT),
+            _ => bail!("unknown country {value:?}, expecting CH, DE, AT, FR or IT")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Currency {
+    CHF,
+    EUR,
+}
+
+/// Shipping cost per order, in the currency of the shop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Shipping {
+    Free,
+    Flat(u16),
+    /// `cost`, unless the order is worth at least `free_from`
+    FreeFrom { cost: u16, free_from: u16 },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum Shop {
-    Digitec,
-    Brack,
+pub struct Shop {
+    pub name: ShopName, // "Digitec"
+    /// Host name of the shop's URLs, without "www."; offers on
+    /// subdomains match too
+    pub url_domain: String, // "digitec.ch"
+    pub country: Country,
+    pub currency: Currency,
+    pub shipping: Value<Shipping>,
+    /// Whether the listed prices include the VAT of `country`
+    pub prices_include_vat: bool,
+}
+
+impl Shop {
+    /// Whether `url` is on the shop's domain (or a subdomain of it).
+    pub fn url_matches(&self, url: &str) -> bool {
+        let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))
+        else { return false };
+        let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+        let host = host.split(':').next().unwrap_or("");
+        host == self.url_domain
+            || host.strip_suffix(&self.url_domain).is_some_and(|sub| sub.ends_with('.'))
+    }
+}
+
+impl PrimaryKey for Shop {
+    type Key = ShopName;
+    fn primary_key(&self) -> &ShopName {
+        &self.name
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Price {
     CHF(u16), // leave out the fractional part
+    EUR(u16),
 }
 
 impl Price {
-    pub fn in_chf(self) -> u16 {
+    pub fn currency(self) -> Currency {
+        match self {
+            Price::CHF(_) => Currency::CHF,
+            Price::EUR(_) => Currency::EUR,
+        }
+    }
+
+    /// In `currency()`
+    pub fn amount(self) -> u16 {
         match self {
-            Price::CHF(v) => v
+            Price::CHF(v) | Price::EUR(v) => v
         }
     }
 }
@@ -210,20 +504,159 @@ impl Price {
 pub struct SoldAt {
     pub article_name: ArticleName, // foreign key!
     pub desc: String, // for double-checking, "AMD Ryzen 9 Pro 7945 Tray Version AM5, 3.70 GHz, 12 -Core"
-    pub shop: Shop,
+    pub shop: ShopName, // foreign key!
     pub url: String, // optional
     pub price: Price,
     pub is_tray_version: bool,
     pub is_used: bool,
+    /// From the shop or, for used offers, the seller; `NotApplicable`
+    /// if sold without warranty
+    pub warranty_months: Value<u8>,
     pub delivered: String, // "Delivered Wed 3.4. Only 1 item in stock"
+    /// The day `delivered` was read, which "tomorrow" and dates without
+    /// a year are relative to
+    pub retrieved: Value<Date>,
+}
+
+impl SoldAt {
+    /// The latest delivery date promised in `delivered`, e.g. 11 April
+    /// for "Delivered between Fri 5.4. and Thu 11.4.", or the day
+    /// after `retrieved` for "Delivered tomorrow". Dates without a
+    /// year are taken to be the first such date on or after
+    /// `retrieved`. None if there is no date or `retrieved` is unknown.
+    pub fn latest_delivery(&self) -> Option<Date> {
+        let retrieved = self.retrieved.get()?;
+        if self.delivered.contains("the day after tomorrow") {
+            return Some(retrieved.add_days(2))
+        }
+        if self.delivered.contains("tomorrow") {
+            return Some(retrieved.add_days(1))
+        }
+        self.delivered.split_whitespace().rev().find_map(|word| {
+            let [day, month, ""] = word.split('.').collect::<Vec<_>>()[..] else { return None };
+            let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+            let date = Date::new(retrieved.year(), month, day).ok()?;
+            if date.unixtime() < retrieved.unixtime() {
+                Date::new(retrieved.year() + 1, month, day).ok()
+            } else {
+                Some(date)
+            }
+        })
+    }
 }
 
-impl PrimaryKey<String> for SoldAt {
+impl PrimaryKey for SoldAt {
+    type Key = String;
     fn primary_key(&self) -> &String {
         &self.url
     }
 }
 
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Motherboard {
+    pub name: ArticleName,
+    pub url: String, // manufacturer's product page
+    pub cpu_socket: CPUSocket,
+    pub memory_type: MemoryType,
+    pub memory_subtype: Value<MemorySubtype>,
+    pub memory_slots: u8,
+    /// Channels wired to the slots; fewer than the CPU's limit the
+    /// memory bandwidth
+    pub memory_channels: u8,
+    pub max_memory_gb: Value<u16>,
+}
+
+impl PrimaryKey for Motherboard {
+    type Key = ArticleName;
+    fn primary_key(&self) -> &ArticleName {
+        &self.name
+    }
+}
+
+/// A set of memory modules sold together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryKit {
+    pub name: ArticleName,
+    pub url: String, // manufacturer's product page
+    pub memory_type: MemoryType,
+    pub memory_subtype: Value<MemorySubtype>,
+    pub modules: u8,
+    pub module_gb: u16,
+    pub speed: MTperSec,
+}
+
+impl MemoryKit {
+    pub fn total_gb(&self) -> u32 {
+        self.modules as u32 * self.module_gb as u32
+    }
+}
+
+impl PrimaryKey for MemoryKit {
+    type Key = ArticleName;
+    fn primary_key(&self) -> &ArticleName {
+        &self.name
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BenchmarkUnit {
+    /// Time taken, lower is better
+    Seconds,
+    /// Throughput of AI inference, higher is better
+    TokensPerSecond,
+    /// Points of a benchmark suite, higher is better
+    Points,
+}
+
+impl BenchmarkUnit {
+    pub fn lower_is_better(self) -> bool {
+        match self {
+            BenchmarkUnit::Seconds => true,
+            BenchmarkUnit::TokensPerSecond | BenchmarkUnit::Points => false,
+        }
+    }
+}
+
+/// A review can have results for many CPUs and benchmarks, hence the
+/// combined key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BenchmarkKey {
+    pub cpu: ArticleName, // foreign key!
+    /// E.g. "Linux kernel build (defconfig)", "Chromium compile"
+    pub benchmark: BenchmarkName,
+    pub source_url: String,
+}
+
+/// A measured result from a review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Benchmark {
+    pub key: BenchmarkKey,
+    pub result: f32,
+    pub units: BenchmarkUnit,
+    /// When the result was published
+    pub date: Value<Date>,
+}
+
+impl Benchmark {
+    /// The result, inverted if lower is better, so that it is
+    /// proportional to speed like the scores.
+    pub fn speed(&self) -> f32 {
+        if self.units.lower_is_better() {
+            1. / self.result
+        } else {
+            self.result
+        }
+    }
+}
+
+impl PrimaryKey for Benchmark {
+    type Key = BenchmarkKey;
+    fn primary_key(&self) -> &BenchmarkKey {
+        &self.key
+    }
+}
+
 impl TryFrom<&str> for Value<Date> {
     type Error = anyhow::Error;
 
@@ -232,13 +665,14 @@ impl TryFrom<&str> for Value<Date> {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Usage {
     Mobile,
     Desktop,
     ServerOrEmbedded,
     Server,
     Embedded,
+    Workstation,
 }
 
 impl TryFrom<&str> for Usage {
@@ -252,6 +686,7 @@ impl TryFrom<&str> for Usage {
             "ServerOrEmbeddedr" => Ok(ServerOrEmbedded),
             "Server" => Ok(Server),
             "Embedded" => Ok(Embedded),
+            "Workstation" => Ok(Workstation),
             _ => bail!("invalid Usage string {value:?}")
         }
     }
@@ -332,6 +767,20 @@ pub enum Brand {
     EPYC9004,
 
     EPYC9005,
+
+    // Intel, by "Product Collection" in ARK. The Core brands are by
+    // generation, not tier (i5/i7/i9 share sockets and architecture).
+    XeonScalable1, // "Intel® Xeon® Scalable Processors"
+    XeonScalable2, // "2nd Generation Intel® Xeon® Scalable Processors"
+    XeonScalable3,
+    XeonScalable4,
+    XeonScalable5,
+    Xeon6, // "Intel® Xeon® 6 Processors"
+    XeonW, // "Intel® Xeon® W Processor"
+    Core12, // "12th Generation Intel® Core™ i9 Processors"
+    Core13,
+    Core14,
+    CoreUltra2, // "Intel® Core™ Ultra Processors (Series 2)"
 }
 
 pub struct BrandInfo {
@@ -340,7 +789,9 @@ pub struct BrandInfo {
     pub codenames: Set<&'static str>,
     pub socket: Set<CPUSocket>,
     /// relative speed of 1 core compared to one of an Epyc 7702 at
-    /// the same frequency
+    /// the same frequency; `NotApplicable` for brands spanning
+    /// several microarchitectures (see `microarchitectures`) or mixing
+    /// core types (Intel P-cores and E-cores)
     pub epyc_speed: Value<f32>,
     pub microarchitectures: Set<Architecture>,
     
@@ -349,18 +800,46 @@ pub struct BrandInfo {
 impl Brand {
     pub fn info(self) -> BrandInfo {
         use Brand::*;
-        use Value::Missing;
+        use Value::NotApplicable;
+        // epyc_speed estimates: Zen 3 is +19% IPC over Zen 2, Zen 4
+        // +13% over Zen 3, Zen 5 +16% over Zen 4 (AMD's claims); Zen 1
+        // is ~15% below Zen 2.
         match self {
-            Ryzen9 => todo!(),
-            RyzenThreadripperPRO5000WX => todo!(),
-            RyzenThreadripper => todo!(),
+            Ryzen9 => BrandInfo {
+                // the Ryzen 9 3900X was the first
+                first_release_year: 2019,
+                usage: Usage::Desktop,
+                codenames: set!["Matisse", "Vermeer", "Raphael", "Granite Ridge"],
+                socket: set![CPUSocket::AM4, CPUSocket::AM5],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::Zen2, Architecture::Zen3,
+                                         Architecture::Zen4, Architecture::Zen5],
+            },
+            RyzenThreadripperPRO5000WX => BrandInfo {
+                first_release_year: 2022,
+                usage: Usage::Workstation,
+                codenames: set!["Chagall"],
+                socket: set![CPUSocket::SWRX8],
+                epyc_speed: 1.2.into(),
+                microarchitectures: set![Architecture::Zen3],
+            },
+            RyzenThreadripper => BrandInfo {
+                first_release_year: 2017,
+                usage: Usage::Desktop,
+                // 1000, 2000, 3000, 7000 series
+                codenames: set!["Whitehaven", "Colfax", "Castle Peak", "Storm Peak"],
+                socket: set![CPUSocket::TR4, CPUSocket::STRX4, CPUSocket::STR5],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::Zen1, // and Zen+
+                                         Architecture::Zen2, Architecture::Zen4],
+            },
 
             EPYC7001 => BrandInfo {
                 first_release_year: 2017,
                 usage: Usage::ServerOrEmbedded,
                 codenames: set!["Naples"],
                 socket: set![CPUSocket::SP3],
-                epyc_speed: Missing,
+                epyc_speed: 0.85.into(),
                 microarchitectures: set![Architecture::Zen1 /*?*/], 
             },
             EPYC7002 => BrandInfo {
@@ -385,7 +864,7 @@ impl Brand {
                 usage: Usage::Server,
                 codenames: set!["Siena"],
                 socket: set![CPUSocket::SP6],
-                epyc_speed: Missing,
+                epyc_speed: 1.35.into(), // same IPC as Zen 4, less cache
                 microarchitectures: set![Architecture::Zen4c],
             },
             EPYC9004 => BrandInfo {
@@ -393,7 +872,7 @@ impl Brand {
                 usage: Usage::Embedded,
                 codenames: set!["Genoa", "Genoa-X", "Bergamo"],
                 socket: set![CPUSocket::SP5],
-                epyc_speed: Missing,
+                epyc_speed: 1.35.into(),
                 microarchitectures: set![Architecture::Zen4], 
             },
             EPYC9005 => BrandInfo {
@@ -401,9 +880,104 @@ impl Brand {
                 usage: Usage::Server,
                 codenames: set!["Turin"],
                 socket: set![CPUSocket::SP5],
-                epyc_speed: Missing,
+                epyc_speed: 1.55.into(),
                 microarchitectures: set![Architecture::Zen5],
             },
+
+            XeonScalable1 => BrandInfo {
+                first_release_year: 2017,
+                usage: Usage::Server,
+                codenames: set!["Skylake-SP"],
+                socket: set![CPUSocket::FCLGA3647],
+                epyc_speed: 0.95.into(),
+                microarchitectures: set![Architecture::Skylake],
+            },
+            XeonScalable2 => BrandInfo {
+                first_release_year: 2019,
+                usage: Usage::Server,
+                codenames: set!["Cascade Lake", "Cascade Lake Refresh"],
+                socket: set![CPUSocket::FCLGA3647],
+                epyc_speed: 0.95.into(), // Skylake cores
+                microarchitectures: set![Architecture::CascadeLake],
+            },
+            XeonScalable3 => BrandInfo {
+                first_release_year: 2020,
+                usage: Usage::Server,
+                codenames: set!["Cooper Lake", "Ice Lake"],
+                socket: set![CPUSocket::FCLGA4189],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::CooperLake, Architecture::IceLake],
+            },
+            XeonScalable4 => BrandInfo {
+                first_release_year: 2023,
+                usage: Usage::Server,
+                codenames: set!["Sapphire Rapids"],
+                socket: set![CPUSocket::FCLGA4677],
+                epyc_speed: 1.3.into(), // Golden Cove cores
+                microarchitectures: set![Architecture::SapphireRapids],
+            },
+            XeonScalable5 => BrandInfo {
+                first_release_year: 2023,
+                usage: Usage::Server,
+                codenames: set!["Emerald Rapids"],
+                socket: set![CPUSocket::FCLGA4677],
+                epyc_speed: 1.3.into(), // Raptor Cove cores
+                microarchitectures: set![Architecture::EmeraldRapids],
+            },
+            Xeon6 => BrandInfo {
+                first_release_year: 2024,
+                usage: Usage::Server,
+                codenames: set!["Granite Rapids", "Sierra Forest"],
+                socket: set![CPUSocket::FCLGA4710, CPUSocket::FCLGA7529],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::GraniteRapids,
+                                         Architecture::SierraForest],
+            },
+            XeonW => BrandInfo {
+                first_release_year: 2017,
+                usage: Usage::Workstation,
+                codenames: set!["Skylake-W", "Cascade Lake-W", "Ice Lake-W",
+                                "Sapphire Rapids-WS"],
+                socket: set![CPUSocket::FCLGA2066, CPUSocket::FCLGA3647,
+                             CPUSocket::FCLGA4189, CPUSocket::FCLGA4677],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::Skylake,
+                                         Architecture::CascadeLake,
+                                         Architecture::IceLake,
+                                         Architecture::SapphireRapids],
+            },
+            Core12 => BrandInfo {
+                first_release_year: 2021,
+                usage: Usage::Desktop,
+                codenames: set!["Alder Lake"],
+                socket: set![CPUSocket::FCLGA1700],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::AlderLake],
+            },
+            Core13 => BrandInfo {
+                first_release_year: 2022,
+                usage: Usage::Desktop,
+                codenames: set!["Raptor Lake"],
+                socket: set![CPUSocket::FCLGA1700],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::RaptorLake],
+            },
+            Core14 => BrandInfo {
+                first_release_year: 2023,
+                usage: Usage::Desktop,
+                codenames: set!["Raptor Lake Refresh"],
+                socket: set![CPUSocket::FCLGA1700],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::RaptorLake],
+            },
+            CoreUltra2 => BrandInfo {
+                first_release_year: 2024,
+                usage: Usage::Desktop,
+                codenames: set!["Arrow Lake"],
+                socket: set![CPUSocket::FCLGA1851],
+                epyc_speed: NotApplicable,
+                microarchitectures: set![Architecture::ArrowLake],
+            },
         }
     }
 }
@@ -424,21 +998,61 @@ impl TryFrom<(&str, u8)> for Brand {
 }
 
 
-/// Specified as "Market segment" or "Platform" by AMD.
+/// Specified as "Market segment" or "Platform" by AMD, "Vertical
+/// Segment" by Intel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MarketSegment {
     EnthusiastDesktop,
+    Desktop,
+    Workstation,
     Server,
 }
 
 
 
-/// Specified as "Product line" by AMD; there's duplication/overlap
+/// Parse Intel's "Product Collection" strings as given in ARK, e.g.
+/// "2nd Generation Intel® Xeon® Scalable Processors" or "13th
+/// Generation Intel® Core™ i9 Processors".
+fn intel_product_line(value: &str) -> anyhow::Result<ProductLine> {
+    let value: String = value.chars().filter(|c| *c != '®' && *c != '™').collect();
+    let (generation, rest): (u8, &str) =
+        if let Some((gen, rest)) = value.split_once(" Generation ") {
+            let digits = gen.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            (digits.parse().with_context(|| anyhow!("generation {gen:?}"))?, rest)
+        } else {
+            (1, &value)
+        };
+    let brand = match (rest, generation) {
+        ("Intel Xeon Scalable Processors", 1) => Brand::XeonScalable1,
+        ("Intel Xeon Scalable Processors", 2) => Brand::XeonScalable2,
+        ("Intel Xeon Scalable Processors", 3) => Brand::XeonScalable3,
+        ("Intel Xeon Scalable Processors", 4) => Brand::XeonScalable4,
+        ("Intel Xeon Scalable Processors", 5) => Brand::XeonScalable5,
+        ("Intel Xeon 6 Processors", 1) => Brand::Xeon6,
+        ("Intel Xeon W Processor", 1) => Brand::XeonW,
+        ("Intel Core Ultra Processors (Series 2)", 1) => Brand::CoreUltra2,
+        ("Intel Core i3 Processors" | "Intel Core i5 Processors"
+         | "Intel Core i7 Processors" | "Intel Core i9 Processors",
+         generation) => match generation {
+            12 => Brand::Core12,
+            13 => Brand::Core13,
+            14 => Brand::Core14,
+            _ => bail!("unknown Intel Core generation {generation}")
+        },
+        _ => bail!("unknown Intel product collection")
+    };
+    let usage = brand.info().usage;
+    Ok(ProductLine(brand, usage.into()))
+}
+
+/// Specified as "Product line" by AMD ("Product Collection" by
+/// Intel); there's duplication/overlap
 /// with `Usage` specification with `Brand`'s `info` method, todo:
 /// verify at runtime. E.g.:
-
-///     "AMD Ryzen™ 9 Desktop Processors" == ProductLine(Brand::Ryzen9, Usage::Desktop)
-
+///
+/// ```text
+/// "AMD Ryzen™ 9 Desktop Processors" == ProductLine(Brand::Ryzen9, Usage::Desktop)
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductLine(pub Brand, pub Value<Usage>);
 
@@ -463,7 +1077,7 @@ impl TryFrom<&str> for Value<ProductLine> {
                 // put in an official roadmap by 2016.[20] "
                     Ok(Value::Some(
                         ProductLine(Brand::RyzenThreadripperPRO5000WX,
-                                    Usage::Desktop.into()))),
+                                    Usage::Workstation.into()))),
                 "AMD Ryzen™ 9 Processors" =>
                     Ok(Value::Some(
                         ProductLine(Brand::Ryzen9,
@@ -501,6 +1115,9 @@ impl TryFrom<&str> for Value<ProductLine> {
 
                     
 
+                _ if value.contains("Intel") =>
+                    Ok(Value::Some(intel_product_line(value)?)),
+
                 _ => {
                     // possibly working more general solution, but I've
                     // given up on parsing, AMD's naming/website is just
@@ -537,3 +1154,90 @@ impl TryFrom<&str> for Value<ProductLine> {
         })().with_context(|| anyhow!("invalid product line string {value:?}"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_intel_product_line() {
+        let brand = |s: &str| -> Brand {
+            let pl: Value<ProductLine> = s.try_into().unwrap();
+            pl.value().unwrap().0
+        };
+        assert!(matches!(brand("2nd Generation Intel® Xeon® Scalable Processors"),
+                         Brand::XeonScalable2));
+        assert!(matches!(brand("Intel® Xeon® Scalable Processors"),
+                         Brand::XeonScalable1));
+        assert!(matches!(brand("13th Generation Intel® Core™ i9 Processors"),
+                         Brand::Core13));
+        assert!(matches!(brand("AMD EPYC™ 7003 Series"), Brand::EPYC7003));
+        let pl: anyhow::Result<Value<ProductLine>> =
+            "7th Generation Intel® Core™ i7 Processors".try_into();
+        assert!(pl.is_err());
+        assert!(matches!("LGA 1700".try_into(), Ok(CPUSocket::FCLGA1700)));
+    }
+
+    #[test]
+    fn t_clusters() {
+        let cpu = CPU::test_epyc_7543p();
+        let clusters = cpu.clusters().unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!((clusters[0].count, clusters[0].threads_per_core), (32, 2));
+
+        let cpu = CPU::test_two_clusters();
+        let clusters = cpu.clusters().unwrap();
+        assert_eq!(clusters.iter().map(|c| c.count).collect::<Vec<_>>(), [8, 24]);
+
+        let conflict = CPU { threads: 64.into(), ..CPU::test_two_clusters() };
+        assert_eq!(conflict.clusters().unwrap_err().to_string(),
+                   "threads is 64, but the core clusters add up to 40");
+        let empty = CPU { core_clusters: vec![].into(), ..CPU::test_two_clusters() };
+        assert_eq!(empty.clusters().unwrap_err().to_string(), "core_clusters is empty");
+        let odd = CPU { threads: 33.into(), ..CPU::test_epyc_7543p() };
+        assert!(odd.clusters().is_err());
+    }
+
+    #[test]
+    fn t_latest_delivery() {
+        let offer = |delivered: &str| SoldAt {
+            article_name: "AMD Ryzen 9 7950X".into(),
+            desc: "".into(),
+            shop: "Digitec".into(),
+            url: "".into(),
+            price: Price::CHF(500),
+            is_tray_version: false,
+            is_used: false,
+            warranty_months: Value::Missing,
+            delivered: delivered.into(),
+            retrieved: Date::new(2024, 4, 1).unwrap().into(),
+        };
+        let day = |y, m, d| Some(Date::new(y, m, d).unwrap().unixtime());
+        let latest = |delivered: &str| offer(delivered).latest_delivery().map(|d| d.unixtime());
+        assert_eq!(latest("Delivered between Fri 5.4. and Thu 11.4.\n7 items in stock"),
+                   day(2024, 4, 11));
+        assert_eq!(latest("Delivered Wed 3.4. Only 1 item in stock"), day(2024, 4, 3));
+        assert_eq!(latest("Delivered tomorrow"), day(2024, 4, 2));
+        assert_eq!(latest("Delivered the day after tomorrow\n10 items in stock"),
+                   day(2024, 4, 3));
+        assert_eq!(latest("Delivered between Fri 29.12. and Tue 2.1."), day(2025, 1, 2));
+        assert_eq!(latest("Not in stock"), None);
+    }
+
+    #[test]
+    fn t_url_matches() {
+        let shop = Shop {
+            name: "Digitec".into(),
+            url_domain: "digitec.ch".into(),
+            country: Country::CH,
+            currency: Currency::CHF,
+            shipping: Value::Missing,
+            prices_include_vat: true,
+        };
+        assert!(shop.url_matches("https://www.digitec.ch/en/s1/product/123"));
+        assert!(shop.url_matches("https://digitec.ch"));
+        assert!(! shop.url_matches("https://www.notdigitec.ch/en"));
+        assert!(! shop.url_matches("https://www.brack.ch/digitec.ch"));
+        assert!(! shop.url_matches("digitec.ch/en"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Monte Carlo propagation of the uncertainty of doubtful inputs (and
+//! of the model's own guesses) to the scores, to see which ranks are
+//! not settled.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::{types::{CPU, Bytes, GHz, MTperSec, ArticleName},
+            value::Value,
+            date::Date,
+            scoring::{Profile, ProfileParams},
+            stats::{Rng, quantile}};
+
+
+#[derive(Debug, Clone)]
+pub struct UncertaintyParams {
+    /// Relative range (±) of numbers marked `SomeWithDoubts`
+    pub doubt_range: f32,
+    /// Days (±) a date marked `SomeWithDoubts` may be off
+    pub doubt_days: i64,
+    /// Relative range (±) of the per-architecture speeds in
+    /// `ProfileParams.ipc`
+    pub ipc_range: f32,
+    /// Relative range (±) of `ProfileParams.doubling_period_years`
+    pub doubling_period_range: f32,
+    pub samples: usize,
+    /// Probability covered by the intervals, e.g. 0.9 for the 5% to
+    /// 95% quantiles
+    pub interval: f32,
+    pub seed: u64,
+}
+
+impl Default for UncertaintyParams {
+    fn default() -> Self {
+        UncertaintyParams {
+            doubt_range: 0.1,
+            doubt_days: 60,
+            // manufacturer claims and reviews vary by workload
+            ipc_range: 0.1,
+            doubling_period_range: 0.3,
+            samples: 500,
+            interval: 0.9,
+            seed: 1,
+        }
+    }
+}
+
+/// Replace a doubtful value by one drawn from its range (which is
+/// then no longer doubtful, as the doubt is accounted for).
+fn draw<T>(v: &mut Value<T>, f: impl FnOnce(&T) -> T) {
+    if let Value::SomeWithDoubts(x, _) = v {
+        *v = Value::Some(f(x));
+    }
+}
+
+fn draw_ghz(v: &mut Value<GHz>, up: &UncertaintyParams, rng: &mut Rng) {
+    draw(v, |x| GHz(x.0 * rng.factor(up.doubt_range)))
+}
+
+fn draw_bytes(v: &mut Value<Bytes>, up: &UncertaintyParams, rng: &mut Rng) {
+    draw(v, |x| {
+        let kb = x.in_bytes() as f32 / 1024. * rng.factor(up.doubt_range);
+        Bytes::KB(kb.round() as u32)
+    })
+}
+
+/// A copy of `cpu` with its doubtful numbers and imprecise dates
+/// drawn from their ranges.
+pub fn perturb_cpu(up: &UncertaintyParams, cpu: &CPU, rng: &mut Rng) -> CPU {
+    let mut cpu = cpu.clone();
+    draw_ghz(&mut cpu.base_clock, up, rng);
+    draw_ghz(&mut cpu.max_boost_clock, up, rng);
+    draw_bytes(&mut cpu.l2cache, up, rng);
+    draw_bytes(&mut cpu.l3cache, up, rng);
+    draw(&mut cpu.system_memory_specification,
+         |x| MTperSec((x.0 as f32 * rng.factor(up.doubt_range)).round() as u32));
+    if let Value::Some(clusters) | Value::SomeWithDoubts(clusters, _) = &mut cpu.core_clusters {
+        for c in clusters {
+            draw_ghz(&mut c.base_clock, up, rng);
+            draw_ghz(&mut c.max_boost_clock, up, rng);
+            draw_bytes(&mut c.l2cache, up, rng);
+            draw_bytes(&mut c.l3cache, up, rng);
+        }
+    }
+    let days = match &cpu.launch_date {
+        Value::Some(d) => d.uncertainty_days(),
+        Value::SomeWithDoubts(d, _) => d.uncertainty_days().max(up.doubt_days),
+        Value::NotApplicable | Value::Missing => 0,
+    };
+    if days > 0 {
+        let shift = (days as f32 * (2. * rng.uniform() - 1.)).round() as i64;
+        let date = match &cpu.launch_date {
+            // stay within the quarter, which isn't exactly 2 x 45 days long
+            Value::Some(d) => {
+                let (first, last) = d.bounds();
+                let days_to = |x: Date| (x.unixtime() - d.unixtime()) / 86400;
+                Some(d.add_days(shift.clamp(days_to(first), days_to(last))))
+            }
+            d => d.get().map(|d| d.add_days(shift)),
+        };
+        if let Some(d) = date {
+            cpu.launch_date = Value::Some(d);
+        }
+    }
+    cpu
+}
+
+/// A copy of `params` with the model's guesses drawn from their
+/// ranges.
+pub fn perturb_params(
+    up: &UncertaintyParams, params: &ProfileParams, rng: &mut Rng
+) -> ProfileParams {
+    let mut params = params.clone();
+    for ipc in params.ipc.values_mut() {
+        *ipc *= rng.factor(up.ipc_range);
+    }
+    params.doubling_period_years *= rng.factor(up.doubling_period_range);
+    params
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Interval {
+    pub low: f32,
+    pub high: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct OfferUncertainty {
+    pub score: Interval,
+    /// Score per CHF of cost
+    pub value: Interval,
+    /// 1 is the best, for the unperturbed values
+    pub rank: usize,
+    /// The best and worst rank within the interval
+    pub rank_best: usize,
+    pub rank_worst: usize,
+}
+
+impl OfferUncertainty {
+    pub fn rank_may_change(&self) -> bool {
+        self.rank_best != self.rank_worst
+    }
+}
+
+fn ranks(values: &[f32]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*b].total_cmp(&values[*a]));
+    let mut ranks = vec![0; values.len()];
+    for (rank, i) in order.into_iter().enumerate() {
+        ranks[i] = rank + 1;
+    }
+    ranks
+}
+
+/// Score and value intervals and the range of ranks by value (score
+/// per cost, in CHF) for each of `offers`, given as CPU, number of
+/// sockets and cost. Offers of the same CPU use the same draw of its
+/// inputs, and all offers the same draw of the model parameters in
+/// each sample.
+pub fn offer_uncertainties(
+    profile: Profile,
+    params: &ProfileParams,
+    up: &UncertaintyParams,
+    offers: &[(&CPU, u8, f32)],
+) -> Result<Vec<OfferUncertainty>> {
+    let mut nominal = Vec::new();
+    for (cpu, sockets, cost) in offers {
+        nominal.push(profile.performance_with_sockets(params, cpu, *sockets)?.score / cost);
+    }
+    let nominal_ranks = ranks(&nominal);
+
+    let mut rng = Rng::new(up.seed);
+    let mut scores = vec![Vec::with_capacity(up.samples); offers.len()];
+    let mut sample_ranks = vec![Vec::with_capacity(up.samples); offers.len()];
+    for _ in 0..up.samples {
+        let params = perturb_params(up, params, &mut rng);
+        let mut cpu_scores: HashMap<(&ArticleName, u8), f32> = HashMap::new();
+        let mut cpus: HashMap<&ArticleName, CPU> = HashMap::new();
+        let mut values = Vec::new();
+        for (i, (cpu, sockets, cost)) in offers.iter().enumerate() {
+            let score = match cpu_scores.get(&(&cpu.name, *sockets)) {
+                Some(score) => *score,
+                None => {
+                    let cpu_s = cpus.entry(&cpu.name)
+                        .or_insert_with(|| perturb_cpu(up, cpu, &mut rng));
+                    let score = profile.performance_with_sockets(&params, cpu_s, *sockets)?
+                        .score;
+                    cpu_scores.insert((&cpu.name, *sockets), score);
+                    score
+                }
+            };
+            scores[i].push(score);
+            values.push(score / cost);
+        }
+        for (i, rank) in ranks(&values).into_iter().enumerate() {
+            sample_ranks[i].push(rank as f32);
+        }
+    }
+
+    let (lo, hi) = ((1. - up.interval) / 2., (1. + up.interval) / 2.);
+    let interval = |xs: &mut Vec<f32>| {
+        xs.sort_by(f32::total_cmp);
+        Interval { low: quantile(xs, lo), high: quantile(xs, hi) }
+    };
+    Ok(offers.iter().enumerate().map(|(i, (_, _, cost))| {
+        let score = interval(&mut scores[i]);
+        let rank = interval(&mut sample_ranks[i]);
+        OfferUncertainty {
+            score,
+            value: Interval { low: score.low / cost, high: score.high / cost },
+            rank: nominal_ranks[i],
+            rank_best: rank.low.round() as usize,
+            rank_worst: rank.high.round() as usize,
+        }
+    }).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::DatePrecision;
+
+    #[test]
+    fn t_ranks() {
+        assert_eq!(ranks(&[1., 3., 2.]), [3, 1, 2]);
+    }
+
+    #[test]
+    fn t_perturb_cpu() {
+        let up = UncertaintyParams::default();
+        let mut rng = Rng::new(up.seed);
+        let cpu = CPU::test_epyc_7543p();
+        let p = perturb_cpu(&up, &cpu, &mut rng);
+        assert_eq!(p.base_clock.get().unwrap().0, cpu.base_clock.get().unwrap().0);
+        assert_eq!(p.launch_date.get().unwrap().unixtime(),
+                   cpu.launch_date.get().unwrap().unixtime());
+
+        let cpu = CPU {
+            base_clock: Value::SomeWithDoubts(GHz(2.8), "test".into()),
+            launch_date: Date::try_from("Q1'21").unwrap().into(),
+            ..cpu
+        };
+        let first = Date::new(2021, 1, 1).unwrap().unixtime();
+        let last = Date::new(2021, 3, 31).unwrap().unixtime();
+        let mut dates = Vec::new();
+        for _ in 0..100 {
+            let p = perturb_cpu(&up, &cpu, &mut rng);
+            let GHz(base) = *p.base_clock.value().unwrap();
+            assert!((2.8 * 0.9 - 1e-3..=2.8 * 1.1 + 1e-3).contains(&base));
+            let date = p.launch_date.value().unwrap();
+            assert_eq!(date.precision(), DatePrecision::Quarter);
+            assert!((first..=last).contains(&date.unixtime()));
+            dates.push(date.unixtime());
+        }
+        assert!(dates.iter().any(|d| *d != dates[0]));
+    }
+
+    #[test]
+    fn t_offer_uncertainties() {
+        let up = UncertaintyParams::default();
+        let params = Profile::Compilation.default_params();
+        let sure = CPU::test_epyc_7543p();
+        let doubtful = CPU {
+            name: "AMD EPYC 7543P (doubtful)".into(),
+            base_clock: Value::SomeWithDoubts(GHz(2.8), "test".into()),
+            ..CPU::test_epyc_7543p()
+        };
+        let u = offer_uncertainties(Profile::Compilation, &params, &up,
+                                    &[(&sure, 1, 2000.), (&doubtful, 1, 1000.)]).unwrap();
+        let width = |i: &Interval| i.high - i.low;
+        assert!(width(&u[1].score) > width(&u[0].score));
+        let nominal = Profile::Compilation.performance(&params, &sure).unwrap().score;
+        for o in &u {
+            assert!(o.score.low < nominal && nominal < o.score.high);
+        }
+        assert_eq!(u[0].value.low, u[0].score.low / 2000.);
+        // the same score at half the price
+        assert_eq!((u[0].rank, u[1].rank), (2, 1));
+        assert!(!u[0].rank_may_change());
+    }
+}
@@ -15,6 +15,15 @@ impl<T> From<T> for Value<T> {
 }
 
 impl<T> Value<T> {
+    /// The value if present, without complaining about doubts or
+    /// missing values (for checks that simply skip those).
+    pub fn get(&self) -> Option<&T> {
+        match self {
+            Value::Some(v) | Value::SomeWithDoubts(v, _) => Some(v),
+            Value::NotApplicable | Value::Missing => None,
+        }
+    }
+
     pub fn value(&self) -> Result<&T> {
         let err = |msg| -> Result<_> {
             match std::env::var("DEBUG_VALUE") {